@@ -132,25 +132,27 @@ pub(crate) mod function {
                     .and_then(|(attrs, cache)| {
                         // If the user wants to see assigned attributes, we always have to match.
                         attributes.is_some().then(|| {
-                            cache.at_entry(entry.path(&index), None).map(|entry| {
-                                let is_excluded = entry.is_excluded();
-                                stats.excluded += usize::from(is_excluded);
-                                let attributes: Vec<_> = {
-                                    last_match = Some(entry.matching_attributes(attrs));
-                                    attrs.iter().map(|m| m.assignment.to_owned()).collect()
-                                };
-                                stats.with_attributes += usize::from(!attributes.is_empty());
-                                stats.max_attributes_per_path = stats.max_attributes_per_path.max(attributes.len());
-                                if let Some(attrs) = repo_attrs.as_mut() {
-                                    attributes.iter().for_each(|attr| {
-                                        attrs.insert(attr.clone());
-                                    });
-                                }
-                                Attrs {
-                                    is_excluded,
-                                    attributes,
-                                }
-                            })
+                            cache
+                                .at_entry(entry.path(&index), Some(entry.mode.is_submodule()))
+                                .map(|entry| {
+                                    let is_excluded = entry.is_excluded();
+                                    stats.excluded += usize::from(is_excluded);
+                                    let attributes: Vec<_> = {
+                                        last_match = Some(entry.matching_attributes(attrs));
+                                        attrs.iter().map(|m| m.assignment.to_owned()).collect()
+                                    };
+                                    stats.with_attributes += usize::from(!attributes.is_empty());
+                                    stats.max_attributes_per_path = stats.max_attributes_per_path.max(attributes.len());
+                                    if let Some(attrs) = repo_attrs.as_mut() {
+                                        attributes.iter().for_each(|attr| {
+                                            attrs.insert(attr.clone());
+                                        });
+                                    }
+                                    Attrs {
+                                        is_excluded,
+                                        attributes,
+                                    }
+                                })
                         })
                     })
                     .transpose()?;
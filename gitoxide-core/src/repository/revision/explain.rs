@@ -207,6 +207,7 @@ impl<'a> delegate::Kind for Explain<'a> {
                 spec::Kind::ExcludeReachable => "exclude",
                 spec::Kind::IncludeReachableFromParents => "include parents",
                 spec::Kind::ExcludeReachableFromParents => "exclude parents",
+                spec::Kind::ExcludeReachableFromParentsOnly => "exclude parents only",
                 spec::Kind::IncludeReachable =>
                     unreachable!("BUG: 'single' mode is implied but cannot be set explicitly"),
             }
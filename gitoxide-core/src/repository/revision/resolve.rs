@@ -41,6 +41,7 @@ pub(crate) mod function {
         mut repo: gix::Repository,
         specs: Vec<OsString>,
         mut out: impl std::io::Write,
+        mut err: impl std::io::Write,
         Options {
             format,
             explain,
@@ -76,6 +77,9 @@ pub(crate) mod function {
                     }
                     let spec = gix::path::os_str_into_bstr(&spec)?;
                     let spec = repo.rev_parse(spec)?;
+                    for warning in spec.warnings() {
+                        writeln!(err, "warning: {warning}")?;
+                    }
                     if cat_file {
                         return display_object(&repo, spec, tree_mode, cache.as_mut().map(|c| (blob_format, c)), out);
                     }
@@ -119,6 +119,15 @@ impl Search {
     pub fn num_pattern_lists(&self) -> usize {
         self.patterns.len()
     }
+
+    /// Return the sources of all pattern lists that contain at least one pattern, in the order they were added in,
+    /// which is the order of ascending precedence.
+    pub fn sources(&self) -> impl Iterator<Item = &Path> + '_ {
+        self.patterns
+            .iter()
+            .filter(|pl| !pl.patterns.is_empty())
+            .filter_map(|pl| pl.source.as_deref())
+    }
 }
 
 impl Pattern for Attributes {
@@ -34,6 +34,7 @@ pub enum Value {
 /// Note that the order is produced after the files are parsed as global ordering is needed that goes beyond the scope of a
 /// single `Search` instance.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrackedAssignment {
     /// The order of the assignment.
     pub id: AttributeId,
@@ -67,6 +68,16 @@ pub struct MatchLocation<'a> {
     pub sequence_number: usize,
 }
 
+/// A pattern that applied to a path during a search, as recorded by an [`Outcome`] if [tracing](Outcome::set_trace())
+/// is enabled.
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Ord, PartialOrd)]
+pub struct TraceEntry<'a> {
+    /// The glob pattern itself, like `/target/*`.
+    pub pattern: &'a gix_glob::Pattern,
+    /// Information about the location of the pattern.
+    pub location: MatchLocation<'a>,
+}
+
 /// The kind of attribute within the context of a [match][Match].
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Ord, PartialOrd)]
 pub enum MatchKind {
@@ -109,6 +120,8 @@ pub struct Outcome {
     /// The amount of attributes that still need to be set, or `None` if this outcome is consumed which means it
     /// needs to be re-initialized.
     remaining: Option<usize>,
+    /// The patterns that applied during the current search along with their location, or `None` if tracing is disabled.
+    trace: Option<Vec<(refmap::RefMapKey, outcome::MatchLocation)>>,
 }
 
 #[derive(Default, Clone)]
@@ -121,6 +134,7 @@ struct Slot {
 
 /// A type to denote an id of an attribute assignment for uniquely identifying each attribute or assignment.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AttributeId(pub usize);
 
 impl Default for AttributeId {
@@ -131,6 +145,7 @@ impl Default for AttributeId {
 
 /// A utility type to collect metadata for each attribute, unified by its name.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetadataCollection {
     /// A mapping of an attribute or macro name to its order, that is the time when it was *first* seen.
     ///
@@ -140,6 +155,7 @@ pub struct MetadataCollection {
 
 /// Metadata associated with an attribute or macro name.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Metadata {
     /// The id to uniquely identify an attribute in the [MetadataCollection].
     pub id: AttributeId,
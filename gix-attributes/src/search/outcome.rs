@@ -70,6 +70,9 @@ impl Outcome {
     pub fn reset(&mut self) {
         self.matches_by_id.iter_mut().for_each(|item| item.r#match = None);
         self.attrs_stack.clear();
+        if let Some(trace) = self.trace.as_mut() {
+            trace.clear();
+        }
         self.reset_remaining();
     }
 
@@ -99,6 +102,7 @@ impl Outcome {
             dest.source_paths = self.source_paths.clone();
         }
         dest.remaining = self.remaining;
+        dest.trace.clone_from(&self.trace);
     }
 }
 
@@ -161,10 +165,34 @@ impl Outcome {
     pub fn is_done(&self) -> bool {
         self.remaining() == 0
     }
+
+    /// Return all patterns that applied during the last search along with their location, in the order they were applied
+    /// in, which is the order of descending precedence.
+    ///
+    /// Patterns only apply if they match and set at least one attribute that wasn't set by a pattern of higher precedence.
+    /// This is empty unless [tracing](Self::set_trace()) is enabled.
+    pub fn trace(&self) -> impl Iterator<Item = crate::search::TraceEntry<'_>> {
+        self.trace
+            .iter()
+            .flatten()
+            .map(|(pattern, location)| crate::search::TraceEntry {
+                pattern: self.patterns.resolve(*pattern).expect("pattern still present"),
+                location: location.to_outer(self),
+            })
+    }
 }
 
 /// Mutation
 impl Outcome {
+    /// If `toggle` is `true`, record each pattern that applies during a search along with its location, to be able to
+    /// explain the outcome similar to `git check-attr`. Otherwise, stop recording and discard everything recorded so far.
+    ///
+    /// The trace is available through [`trace()`](Self::trace()) and cleared on each [reset](Self::reset()).
+    pub fn set_trace(&mut self, toggle: bool) -> &mut Self {
+        self.trace = toggle.then(|| self.trace.take().unwrap_or_default());
+        self
+    }
+
     /// Fill all `attrs` and resolve them recursively if they are macros. Return `true` if there is no attribute left to be resolved and
     /// we are totally done.
     /// `pattern` is what matched a patch and is passed for contextual information,
@@ -176,6 +204,15 @@ impl Outcome {
         source: Option<&std::path::PathBuf>,
         sequence_number: usize,
     ) -> bool {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push((
+                self.patterns.insert(pattern),
+                MatchLocation {
+                    source: source.map(|path| self.source_paths.insert(path)),
+                    sequence_number,
+                },
+            ));
+        }
         self.attrs_stack.extend(
             attrs
                 .filter(|attr| self.matches_by_id[attr.id.0].r#match.is_none())
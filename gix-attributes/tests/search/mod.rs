@@ -266,11 +266,47 @@ fn given_attributes_are_made_available_in_given_order() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn trace_lists_the_patterns_that_applied_in_order_of_precedence() {
+    use std::path::Path;
+
+    let mut search = gix_attributes::Search::default();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    for (content, source) in [(&b"* a b\n*.txt c\n"[..], "first"), (b"*.rs a=rust\nx* b\n", "second")] {
+        search.add_patterns_buffer(content, source.into(), None, &mut collection, true);
+    }
+    let mut out = Outcome::default();
+    out.initialize(&collection);
+    assert_eq!(out.trace().count(), 0, "tracing is disabled by default");
+
+    out.set_trace(true);
+    assert!(search.pattern_matching_relative_path("file.rs".into(), Case::Sensitive, None, &mut out));
+    let actual: Vec<_> = out
+        .trace()
+        .map(|e| (e.pattern.to_string(), e.location.source, e.location.sequence_number))
+        .collect();
+    assert_eq!(
+        actual,
+        [
+            ("*.rs".to_string(), Some(Path::new("second")), 1),
+            ("*".to_string(), Some(Path::new("first")), 1)
+        ],
+        "patterns of higher precedence come first, and the second pattern applies as it sets `b`"
+    );
+
+    out.reset();
+    assert_eq!(out.trace().count(), 0, "the trace is cleared for each search");
+
+    out.set_trace(false);
+    search.pattern_matching_relative_path("file.rs".into(), Case::Sensitive, None, &mut out);
+    assert_eq!(out.trace().count(), 0, "nothing is recorded once tracing is disabled");
+}
+
 #[test]
 fn size_of_outcome() {
     assert_eq!(
         std::mem::size_of::<Outcome>(),
-        904,
+        928,
         "it's quite big, shouldn't change without us noticing"
     )
 }
@@ -77,6 +77,8 @@ pub struct Stack {
     valid_components: usize,
     /// If set, we assume the `current` element is a directory to affect calls to `(push|pop)_directory()`.
     current_is_directory: bool,
+    /// If set, `push_directory()` was called for the root already, which happens only once as it's never popped.
+    root_is_pushed: bool,
 }
 
 #[cfg(unix)]
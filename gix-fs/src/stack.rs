@@ -49,6 +49,7 @@ impl Stack {
             valid_components: 0,
             root,
             current_is_directory: true,
+            root_is_pushed: false,
         }
     }
 
@@ -64,8 +65,9 @@ impl Stack {
             "only index paths are handled correctly here, must be relative"
         );
 
-        if self.valid_components == 0 {
+        if !self.root_is_pushed {
             delegate.push_directory(self)?;
+            self.root_is_pushed = true;
         }
 
         let mut components = relative.components().peekable();
@@ -114,4 +116,41 @@ impl Stack {
         }
         Ok(())
     }
+
+    /// Like [`make_relative_path_current()`](Self::make_relative_path_current()), but `relative` is known to be a directory,
+    /// so `push_directory()` is called for it as well as if a path within it was set.
+    ///
+    /// This allows to enter directories one at a time, and to leave them by setting one of their leading directories.
+    pub fn make_relative_directory_current(
+        &mut self,
+        relative: &Path,
+        delegate: &mut dyn Delegate,
+    ) -> std::io::Result<()> {
+        self.make_relative_path_current(relative, delegate)?;
+        if !self.current_is_directory {
+            delegate.push_directory(self)?;
+            self.current_is_directory = true;
+        }
+        Ok(())
+    }
+
+    /// Leave the current directory, or the directory of the current file, to make its parent directory current,
+    /// and call `pop_directory()` for each directory that was left.
+    ///
+    /// Nothing happens if the root is current.
+    pub fn pop_directory(&mut self, delegate: &mut dyn Delegate) {
+        if !self.current_is_directory {
+            self.current.pop();
+            self.current_relative.pop();
+            self.valid_components -= 1;
+            self.current_is_directory = true;
+        }
+        if self.valid_components == 0 {
+            return;
+        }
+        self.current.pop();
+        self.current_relative.pop();
+        self.valid_components -= 1;
+        delegate.pop_directory();
+    }
 }
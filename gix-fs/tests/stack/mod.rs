@@ -154,3 +154,51 @@ fn delegate_calls_are_consistent() -> crate::Result {
 
     Ok(())
 }
+
+#[test]
+fn directories_can_be_entered_and_left_one_at_a_time() -> crate::Result {
+    let root = PathBuf::from(".");
+    let mut s = Stack::new(root.clone());
+    let mut r = Record::default();
+
+    s.pop_directory(&mut r);
+    assert_eq!(r, Record::default(), "there is nothing to leave if nothing was entered");
+
+    s.make_relative_directory_current("a".as_ref(), &mut r)?;
+    assert_eq!(r.dirs, [root.clone(), root.join("a")]);
+
+    s.make_relative_directory_current("a/b".as_ref(), &mut r)?;
+    assert_eq!(r.dirs, [root.clone(), root.join("a"), root.join("a").join("b")]);
+
+    s.make_relative_path_current("a/b/file".as_ref(), &mut r)?;
+    assert_eq!(
+        r.push_dir, 3,
+        "files in the current directory don't cause the directory to be pushed again"
+    );
+
+    s.pop_directory(&mut r);
+    assert_eq!(s.current_relative(), Path::new("a"));
+    assert_eq!(
+        r.dirs,
+        [root.clone(), root.join("a")],
+        "the directory of the current file is left"
+    );
+
+    s.make_relative_directory_current("a/c".as_ref(), &mut r)?;
+    assert_eq!(r.dirs, [root.clone(), root.join("a"), root.join("a").join("c")]);
+
+    s.pop_directory(&mut r);
+    s.pop_directory(&mut r);
+    assert_eq!(s.current_relative(), Path::new(""));
+    assert_eq!(r.dirs.len(), 1, "the root directory remains");
+
+    s.pop_directory(&mut r);
+    s.make_relative_directory_current("d".as_ref(), &mut r)?;
+    assert_eq!(
+        r.dirs,
+        [root.clone(), root.join("d")],
+        "the root is only pushed once, even if it was current"
+    );
+    assert_eq!(r.push_dir, 5);
+    Ok(())
+}
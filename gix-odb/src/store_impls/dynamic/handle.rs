@@ -136,10 +136,12 @@ pub(crate) mod index_lookup {
         }
 
         /// Call `lookup_prefix(…)` on either index or multi-index, and transform matches into an object id.
+        /// Add no more than the amount of ids to `candidates` that makes it hold `limit` of them.
         pub(crate) fn lookup_prefix(
             &self,
             prefix: gix_hash::Prefix,
             candidates: Option<&mut HashSet<gix_hash::ObjectId>>,
+            limit: usize,
         ) -> Option<crate::store::prefix::lookup::Outcome> {
             let mut candidate_entries = candidates.as_ref().map(|_| 0..0);
             let res = match &self.file {
@@ -152,7 +154,8 @@ pub(crate) mod index_lookup {
             }?;
 
             if let Some((candidates, entries)) = candidates.zip(candidate_entries) {
-                candidates.extend(entries.map(|entry| self.oid_at_index(entry).to_owned()));
+                let remaining = limit.saturating_sub(candidates.len());
+                candidates.extend(entries.map(|entry| self.oid_at_index(entry).to_owned()).take(remaining));
             }
             Some(res.map(|entry_index| self.oid_at_index(entry_index).to_owned()))
         }
@@ -17,6 +17,8 @@ pub mod lookup {
         LooseWalkDir(#[from] loose::iter::Error),
         #[error(transparent)]
         LoadIndex(#[from] crate::store::load_index::Error),
+        #[error("The deadline for the lookup passed before it was complete")]
+        DeadlineExceeded,
     }
 
     /// A way to indicate if a lookup, despite successful, was ambiguous or yielded exactly
@@ -142,27 +144,85 @@ where
     /// - If `candidates` is `Some(…)`, the traversal will continue to obtain all candidates, which takes more time
     ///   as there is no early abort.
     pub fn lookup_prefix(
+        &self,
+        prefix: gix_hash::Prefix,
+        candidates: Option<&mut HashSet<gix_hash::ObjectId>>,
+    ) -> Result<Option<lookup::Outcome>, lookup::Error> {
+        self.lookup_prefix_inner(prefix, candidates, usize::MAX, &mut || false)
+    }
+
+    /// Like [`lookup_prefix()`](Self::lookup_prefix()), but collect no more than `limit` object ids into `candidates`
+    /// and stop the search as soon as that many were found.
+    ///
+    /// This bounds the time and memory spent on very short prefixes which may match a large portion of all objects.
+    /// If `candidates` holds `limit` ids afterwards, there may be more objects matching `prefix`.
+    /// `limit` is at least 2 so ambiguity can still be detected.
+    pub fn lookup_prefix_with_limit(
+        &self,
+        prefix: gix_hash::Prefix,
+        candidates: &mut HashSet<gix_hash::ObjectId>,
+        limit: usize,
+    ) -> Result<Option<lookup::Outcome>, lookup::Error> {
+        self.lookup_prefix_inner(prefix, Some(candidates), limit.max(2), &mut || false)
+    }
+
+    /// Like [`lookup_prefix_with_limit()`](Self::lookup_prefix_with_limit()), but give up with
+    /// [`DeadlineExceeded`](lookup::Error::DeadlineExceeded) once `deadline_exceeded()` returns `true`,
+    /// for instance because a point in time has passed.
+    ///
+    /// It's called before searching each index and for each loose object, so the lookup may take a little longer
+    /// than the deadline, but doesn't hang on large object databases.
+    pub fn lookup_prefix_with_deadline(
+        &self,
+        prefix: gix_hash::Prefix,
+        candidates: &mut HashSet<gix_hash::ObjectId>,
+        limit: usize,
+        mut deadline_exceeded: impl FnMut() -> bool,
+    ) -> Result<Option<lookup::Outcome>, lookup::Error> {
+        self.lookup_prefix_inner(prefix, Some(candidates), limit.max(2), &mut deadline_exceeded)
+    }
+
+    fn lookup_prefix_inner(
         &self,
         prefix: gix_hash::Prefix,
         mut candidates: Option<&mut HashSet<gix_hash::ObjectId>>,
+        limit: usize,
+        deadline_exceeded: &mut dyn FnMut() -> bool,
     ) -> Result<Option<lookup::Outcome>, lookup::Error> {
         let mut candidate: Option<gix_hash::ObjectId> = None;
         loop {
             let snapshot = self.snapshot.borrow();
             for index in &snapshot.indices {
+                if deadline_exceeded() {
+                    return Err(lookup::Error::DeadlineExceeded);
+                }
                 #[allow(clippy::needless_option_as_deref)] // needed as it's the equivalent of a reborrow.
-                let lookup_result = index.lookup_prefix(prefix, candidates.as_deref_mut());
+                let lookup_result = index.lookup_prefix(prefix, candidates.as_deref_mut(), limit);
                 if candidates.is_none() && !check_candidate(lookup_result, &mut candidate) {
                     return Ok(Some(Err(())));
                 }
+                if candidates.as_ref().map_or(false, |c| c.len() >= limit) {
+                    return Ok(Some(Err(())));
+                }
             }
 
             for lodb in snapshot.loose_dbs.iter() {
+                let mut exceeded = false;
                 #[allow(clippy::needless_option_as_deref)] // needed as it's the equivalent of a reborrow.
-                let lookup_result = lodb.lookup_prefix(prefix, candidates.as_deref_mut())?;
+                let lookup_result =
+                    lodb.lookup_prefix_with_limit_and_deadline(prefix, candidates.as_deref_mut(), limit, &mut || {
+                        exceeded = deadline_exceeded();
+                        exceeded
+                    })?;
+                if exceeded {
+                    return Err(lookup::Error::DeadlineExceeded);
+                }
                 if candidates.is_none() && !check_candidate(lookup_result, &mut candidate) {
                     return Ok(Some(Err(())));
                 }
+                if candidates.as_ref().map_or(false, |c| c.len() >= limit) {
+                    return Ok(Some(Err(())));
+                }
             }
 
             match self.store.load_one_index(self.refresh, snapshot.marker)? {
@@ -48,9 +48,21 @@ impl Store {
     /// Pass `candidates` to obtain the set of all object ids matching `prefix`, with the same return value as
     /// one would have received if it remained `None`.
     pub fn lookup_prefix(
+        &self,
+        prefix: gix_hash::Prefix,
+        candidates: Option<&mut HashSet<gix_hash::ObjectId>>,
+    ) -> Result<Option<crate::store::prefix::lookup::Outcome>, crate::loose::iter::Error> {
+        self.lookup_prefix_with_limit_and_deadline(prefix, candidates, usize::MAX, &mut || false)
+    }
+
+    /// Like [`lookup_prefix()`](Self::lookup_prefix()), but stop the traversal once `candidates` holds `limit` object ids,
+    /// or once `deadline_exceeded()` returns `true`, which is called for each object.
+    pub(crate) fn lookup_prefix_with_limit_and_deadline(
         &self,
         prefix: gix_hash::Prefix,
         mut candidates: Option<&mut HashSet<gix_hash::ObjectId>>,
+        limit: usize,
+        deadline_exceeded: &mut dyn FnMut() -> bool,
     ) -> Result<Option<crate::store::prefix::lookup::Outcome>, crate::loose::iter::Error> {
         let single_directory_iter = crate::loose::Iter {
             inner: gix_features::fs::walkdir_new(
@@ -66,6 +78,9 @@ impl Store {
         };
         let mut candidate = None;
         for oid in single_directory_iter {
+            if deadline_exceeded() {
+                break;
+            }
             let oid = match oid {
                 Ok(oid) => oid,
                 Err(err) => {
@@ -79,6 +94,9 @@ impl Store {
                 match &mut candidates {
                     Some(candidates) => {
                         candidates.insert(oid);
+                        if candidates.len() >= limit {
+                            break;
+                        }
                     }
                     None => {
                         if candidate.is_some() {
@@ -702,6 +702,69 @@ mod lookup_prefix {
         assert_all_indices_loaded(&handle, 2, 2);
     }
 
+    #[test]
+    fn candidates_can_be_limited() {
+        let (handle, _tmp) = db_with_all_object_sources().unwrap();
+        let input_id = hex_to_id("a7065b5e971a6d8b55875d8cf634a3a37202ab23");
+        let prefix = gix_hash::Prefix::new(&input_id, 4).unwrap();
+
+        let mut candidates = HashSet::default();
+        assert_eq!(
+            handle.lookup_prefix_with_limit(prefix, &mut candidates, 0).unwrap(),
+            Some(Err(())),
+            "the limit is at least two so ambiguity is still detected"
+        );
+        assert_eq!(candidates.len(), 2, "there are only two objects with that prefix");
+
+        candidates.clear();
+        assert_eq!(
+            handle
+                .lookup_prefix_with_limit(prefix, &mut candidates, usize::MAX)
+                .unwrap(),
+            Some(Err(())),
+        );
+        assert_eq!(candidates.len(), 2, "a limit that isn't reached has no effect");
+    }
+
+    #[test]
+    fn lookups_can_give_up_after_a_deadline() {
+        let (handle, _tmp) = db_with_all_object_sources().unwrap();
+        let input_id = hex_to_id("a7065b5e971a6d8b55875d8cf634a3a37202ab23");
+        let prefix = gix_hash::Prefix::new(&input_id, 4).unwrap();
+
+        let mut candidates = HashSet::default();
+        let mut steps = 0;
+        assert_eq!(
+            handle
+                .lookup_prefix_with_deadline(prefix, &mut candidates, usize::MAX, || {
+                    steps += 1;
+                    false
+                })
+                .unwrap(),
+            Some(Err(())),
+        );
+        assert_eq!(candidates.len(), 2, "a deadline that isn't reached has no effect");
+        assert!(steps > 2, "the deadline is checked for each index and loose object");
+
+        candidates.clear();
+        let start = std::time::Instant::now();
+        let budget = std::time::Duration::from_millis(25);
+        let mut steps_taken = 0;
+        assert!(
+            matches!(
+                handle.lookup_prefix_with_deadline(prefix, &mut candidates, usize::MAX, || {
+                    // Each step of searching this object database takes a while.
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                    steps_taken += 1;
+                    start.elapsed() >= budget
+                }),
+                Err(gix_odb::store::prefix::lookup::Error::DeadlineExceeded)
+            ),
+            "a slow object database is searched only until the deadline passed"
+        );
+        assert!(steps_taken < steps, "the lookup stopped early");
+    }
+
     #[test]
     fn iterable_objects_can_be_looked_up_with_varying_prefix_lengths() -> crate::Result {
         let (mut handle, _tmp) = db_with_all_object_sources()?;
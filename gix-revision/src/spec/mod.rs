@@ -18,6 +18,8 @@ pub enum Kind {
     IncludeReachableFromParents,
     /// Exclude every commit of all parents of `a`, but not `a` itself. Example: `a^!`.
     ExcludeReachableFromParents,
+    /// Exclude every commit of all parents of `a`, without including `a` itself. Example: `^a^@`.
+    ExcludeReachableFromParentsOnly,
 }
 
 impl Spec {
@@ -30,6 +32,7 @@ impl Spec {
             Spec::Merge { .. } => Kind::ReachableToMergeBase,
             Spec::IncludeOnlyParents { .. } => Kind::IncludeReachableFromParents,
             Spec::ExcludeParents { .. } => Kind::ExcludeReachableFromParents,
+            Spec::ExcludeOnlyParents { .. } => Kind::ExcludeReachableFromParentsOnly,
         }
     }
 }
@@ -48,6 +51,7 @@ mod _impls {
                 Spec::Merge { theirs, ours } => write!(f, "{theirs}...{ours}"),
                 Spec::IncludeOnlyParents(from_exclusive) => write!(f, "{from_exclusive}^@"),
                 Spec::ExcludeParents(oid) => write!(f, "{oid}^!"),
+                Spec::ExcludeOnlyParents(oid) => write!(f, "^{oid}^@"),
             }
         }
     }
@@ -101,6 +105,13 @@ pub(crate) mod types {
             /// Exclude the parents of this object, but not the object itself.
             gix_hash::ObjectId,
         ),
+        /// Exclude every commit of all parents of `a`, without including `a` itself. Example: `^a^@`.
+        ///
+        /// The equivalent to [crate::spec::Kind::ExcludeReachableFromParentsOnly], but with data.
+        ExcludeOnlyParents(
+            /// Exclude the parents of this object, which itself is neither included nor excluded.
+            gix_hash::ObjectId,
+        ),
     }
 }
 
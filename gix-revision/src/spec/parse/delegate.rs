@@ -24,7 +24,7 @@ pub trait Revision {
     fn reflog(&mut self, query: ReflogLookup) -> Option<()>;
 
     /// When looking at `HEAD`, `branch_no` is the non-null checkout in the path, e.g. `1` means the last branch checked out,
-    /// `2` is the one before that.
+    /// `2` is the one before that. The value will never be `0` as `@{-0}` is rejected by the parser, just like in `git`.
     /// Return `None` if there is no branch as the checkout history (via the reflog) isn't long enough.
     fn nth_checked_out_branch(&mut self, branch_no: usize) -> Option<()>;
 
@@ -40,6 +40,10 @@ pub trait Revision {
 /// Combine one or more specs into a range of multiple.
 pub trait Kind {
     /// Set the kind of the spec, which happens only once if it happens at all.
+    /// The only exception is `^rev^@`, which first sets [`spec::Kind::ExcludeReachable`][crate::spec::Kind::ExcludeReachable]
+    /// and then [`spec::Kind::ExcludeReachableFromParentsOnly`][crate::spec::Kind::ExcludeReachableFromParentsOnly].
+    /// Implementations must accept this second call and let its kind replace the first one, instead of rejecting it
+    /// as a kind that was set twice.
     /// In case this method isn't called, assume `Single`.
     /// Reject a kind by returning `None` to stop the parsing.
     ///
@@ -120,6 +124,8 @@ pub enum PeelTo<'a> {
     /// Ensure the object at hand exists and is valid (actually without peeling it),
     /// without imposing any restrictions to its type.
     /// The object needs to be looked up to assure that it is valid, but it doesn't need to be decoded.
+    /// If the object is replaced via `refs/replace/`, its id is retained just like in `git`, while its content is
+    /// the one of the replacement.
     ValidObject,
     /// Follow an annotated tag object recursively until an object is found.
     RecursiveTagObject,
@@ -100,6 +100,7 @@ mod intercept {
         pub inner: &'a mut T,
         pub last_ref: Option<BString>, // TODO: smallvec to save the unnecessary allocation? Can't keep ref due to lifetime constraints in traits
         pub last_prefix: Option<(gix_hash::Prefix, Option<PrefixHintOwned>)>,
+        pub kind: Option<crate::spec::Kind>,
         pub done: bool,
     }
 
@@ -112,6 +113,7 @@ mod intercept {
                 inner: delegate,
                 last_ref: None,
                 last_prefix: None,
+                kind: None,
                 done: false,
             }
         }
@@ -184,6 +186,7 @@ mod intercept {
         T: Delegate,
     {
         fn kind(&mut self, kind: crate::spec::Kind) -> Option<()> {
+            self.kind = Some(kind);
             self.inner.kind(kind)
         }
     }
@@ -567,9 +570,12 @@ where
                     delegate.done();
                     return Ok(input[cursor + 1..].as_bstr());
                 } else if past_sep.and_then(<[_]>::first) == Some(&b'@') {
-                    delegate
-                        .kind(spec::Kind::IncludeReachableFromParents)
-                        .ok_or(Error::Delegate)?;
+                    let kind = if delegate.kind == Some(spec::Kind::ExcludeReachable) {
+                        spec::Kind::ExcludeReachableFromParentsOnly
+                    } else {
+                        spec::Kind::IncludeReachableFromParents
+                    };
+                    delegate.kind(kind).ok_or(Error::Delegate)?;
                     delegate.done();
                     return Ok(input[cursor + 1..].as_bstr());
                 } else {
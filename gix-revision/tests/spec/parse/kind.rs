@@ -61,6 +61,38 @@ mod include_parents {
     }
 }
 
+mod exclude_parents_only {
+    use gix_revision::spec;
+
+    use crate::spec::parse::{kind::prefix, parse, try_parse, Call};
+
+    #[test]
+    fn leading_caret_and_trailing_caret_at_symbol() {
+        let rec = parse("^HEAD^@");
+        assert_eq!(rec.kind.unwrap(), spec::Kind::ExcludeReachableFromParentsOnly);
+        assert_eq!(rec.get_ref(0), "HEAD");
+        assert_eq!(rec.prefix[0], None);
+        assert_eq!(
+            rec.order,
+            [Call::Kind, Call::FindRef, Call::Kind],
+            "the exclusion is refined once the parents are requested"
+        );
+        assert!(rec.done);
+
+        let rec = parse("^abcd^@");
+        assert_eq!(rec.kind.unwrap(), spec::Kind::ExcludeReachableFromParentsOnly);
+        assert_eq!(rec.prefix[0], prefix("abcd").into());
+        assert_eq!(rec.order, [Call::Kind, Call::DisambiguatePrefix, Call::Kind]);
+        assert!(rec.done);
+    }
+
+    #[test]
+    fn trailing_caret_at_symbol_must_end_the_input() {
+        let err = try_parse("^r1^@~1").unwrap_err();
+        assert!(matches!(err, spec::parse::Error::UnconsumedInput { .. }));
+    }
+}
+
 mod exclude_parents {
     use gix_revision::spec;
 
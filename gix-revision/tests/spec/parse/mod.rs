@@ -185,7 +185,9 @@ impl delegate::Kind for Recorder {
         if self.opts.reject_kind {
             return None;
         }
-        if self.kind.is_none() {
+        if self.kind.is_none()
+            || (self.kind == Some(spec::Kind::ExcludeReachable) && kind == spec::Kind::ExcludeReachableFromParentsOnly)
+        {
             self.kind = Some(kind);
         } else if !self.opts.no_internal_assertions {
             panic!("called kind more than once with '{kind:?}'");
@@ -55,3 +55,18 @@ fn paths_have_to_be_last_but_stack_with_other_navigation() {
     );
     assert_eq!(rec.calls, 6);
 }
+
+#[test]
+fn colons_after_a_ref_name_start_a_path_as_ref_names_cannot_contain_them() {
+    let rec = parse("refs/weird:name");
+    assert!(rec.kind.is_none());
+    assert_eq!(rec.get_ref(0), "refs/weird");
+    assert_eq!(rec.peel_to, vec![PeelTo::Path("name".into())]);
+    assert_eq!(rec.calls, 2);
+
+    let rec = parse("main:dir/a..b");
+    assert!(rec.kind.is_none(), "paths aren't split into ranges");
+    assert_eq!(rec.get_ref(0), "main");
+    assert_eq!(rec.find_ref[1], None, "there is no second endpoint");
+    assert_eq!(rec.peel_to, vec![PeelTo::Path("dir/a..b".into())]);
+}
@@ -269,6 +269,15 @@ impl<'index> State<'_, 'index> {
             return None;
         }
         let path = entry.path_in(self.path_backing);
+        if !self
+            .attr_stack
+            .state()
+            .ignore()
+            .map_or(true, |ignore| ignore.is_in_sparse_checkout(path))
+        {
+            self.skipped_by_entry_flags.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
         let is_excluded = pathspec
             .pattern_matching_relative_path(
                 path,
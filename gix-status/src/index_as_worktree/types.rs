@@ -43,6 +43,9 @@ pub struct Context<'a> {
     pub pathspec: gix_pathspec::Search,
     /// A stack pre-configured to allow accessing attributes for each entry, as required for `filter`
     /// and possibly pathspecs.
+    ///
+    /// If it was created with [`State::for_status()`](gix_worktree::stack::State::for_status()) and a sparse checkout,
+    /// entries outside of it are skipped just like entries with the skip-worktree bit.
     pub stack: gix_worktree::Stack,
     /// A filter to be able to perform conversions from and to the worktree format.
     ///
@@ -67,7 +70,8 @@ pub struct Outcome {
     pub entries_skipped_by_common_prefix: usize,
     /// The amount of entries that were skipped due to exclusion by *pathspecs*.
     pub entries_skipped_by_pathspec: usize,
-    /// The amount of entries that were skipped as the entry flag indicated this.
+    /// The amount of entries that were skipped as the entry flag indicated this, or as they are outside of the sparse
+    /// checkout that the `stack` was configured with.
     pub entries_skipped_by_entry_flags: usize,
     /// The amount of times we queried symlink-metadata for a file on disk.
     pub symlink_metadata_calls: usize,
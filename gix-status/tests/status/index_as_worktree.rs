@@ -42,23 +42,60 @@ fn fixture_with_index(
     prepare_index: impl FnMut(&mut gix_index::State),
     expected_status: &[Expectation<'_>],
 ) -> Outcome {
-    fixture_filtered_detailed(name, "", &[], expected_status, prepare_index, false)
+    fixture_with_index_and_state(name, prepare_index, attributes_stack(), expected_status)
+}
+
+fn fixture_with_index_and_state(
+    name: &str,
+    prepare_index: impl FnMut(&mut gix_index::State),
+    state: gix_worktree::stack::State,
+    expected_status: &[Expectation<'_>],
+) -> Outcome {
+    fixture_filtered_detailed(name, "", &[], expected_status, prepare_index, false, state)
+}
+
+fn attributes_stack() -> gix_worktree::stack::State {
+    gix_worktree::stack::State::AttributesStack(Default::default())
 }
 
 fn submodule_fixture(name: &str, expected_status: &[Expectation<'_>]) -> Outcome {
-    fixture_filtered_detailed("status_submodule", name, &[], expected_status, |_| {}, false)
+    fixture_filtered_detailed(
+        "status_submodule",
+        name,
+        &[],
+        expected_status,
+        |_| {},
+        false,
+        attributes_stack(),
+    )
 }
 
 fn conflict_fixture(name: &str, expected_status: &[Expectation<'_>]) -> Outcome {
-    fixture_filtered_detailed("conflicts", name, &[], expected_status, |_| {}, false)
+    fixture_filtered_detailed(
+        "conflicts",
+        name,
+        &[],
+        expected_status,
+        |_| {},
+        false,
+        attributes_stack(),
+    )
 }
 
 fn submodule_fixture_status(name: &str, expected_status: &[Expectation<'_>], submodule_dirty: bool) -> Outcome {
-    fixture_filtered_detailed("status_submodule", name, &[], expected_status, |_| {}, submodule_dirty)
+    fixture_filtered_detailed(
+        "status_submodule",
+        name,
+        &[],
+        expected_status,
+        |_| {},
+        submodule_dirty,
+        attributes_stack(),
+    )
 }
 
 fn fixture_filtered(name: &str, pathspecs: &[&str], expected_status: &[Expectation<'_>]) -> Outcome {
-    fixture_filtered_detailed(name, "", pathspecs, expected_status, |_| {}, false)
+    fixture_filtered_detailed(name, "", pathspecs, expected_status, |_| {}, false, attributes_stack())
 }
 
 fn fixture_filtered_detailed(
@@ -68,6 +105,7 @@ fn fixture_filtered_detailed(
     expected_status: &[Expectation<'_>],
     mut prepare_index: impl FnMut(&mut gix_index::State),
     submodule_dirty: bool,
+    state: gix_worktree::stack::State,
 ) -> Outcome {
     // This can easily happen in some fixtures, which can cause flakiness. It's time-dependent after all.
     fn ignore_racyclean(mut out: Outcome) -> Outcome {
@@ -94,13 +132,8 @@ fn fixture_filtered_detailed(
     let mut recorder = Recorder::default();
     let search = gix_pathspec::Search::from_specs(to_pathspecs(pathspecs), None, std::path::Path::new(""))
         .expect("valid specs can be normalized");
-    let stack = gix_worktree::Stack::from_state_and_ignore_case(
-        worktree.clone(),
-        false,
-        gix_worktree::stack::State::AttributesStack(Default::default()),
-        &index,
-        index.path_backing(),
-    );
+    let stack =
+        gix_worktree::Stack::from_state_and_ignore_case(worktree.clone(), false, state, &index, index.path_backing());
     let outcome = index_as_worktree(
         &index,
         &worktree,
@@ -226,6 +259,63 @@ fn removed() {
     );
 }
 
+#[test]
+fn skip_worktree_entries_are_not_reported_as_removed() {
+    let out = fixture_with_index(
+        "status_removed",
+        |index| {
+            for (entry, _) in index
+                .entries_mut_with_paths()
+                .filter(|(_, path)| path.starts_with(b"dir/"))
+            {
+                entry.flags |= gix_index::entry::Flags::SKIP_WORKTREE | gix_index::entry::Flags::EXTENDED;
+            }
+        },
+        &[
+            (BStr::new(b"empty"), 2, status_removed()),
+            (BStr::new(b"executable"), 3, status_removed()),
+        ],
+    );
+    assert_eq!(
+        out,
+        Outcome {
+            entries_to_process: 4,
+            entries_processed: 4,
+            entries_skipped_by_entry_flags: 2,
+            symlink_metadata_calls: 2,
+            ..Default::default()
+        },
+        "entries excluded by a sparse checkout are never checked in the worktree"
+    );
+}
+
+#[test]
+fn entries_outside_of_the_sparse_checkout_are_not_reported_as_removed() {
+    let out = fixture_with_index_and_state(
+        "status_removed",
+        |_| {},
+        gix_worktree::stack::State::for_status(
+            Default::default(),
+            Some(std::sync::Arc::new(|path: &BStr| !path.starts_with(b"dir/"))),
+        ),
+        &[
+            (BStr::new(b"empty"), 2, status_removed()),
+            (BStr::new(b"executable"), 3, status_removed()),
+        ],
+    );
+    assert_eq!(
+        out,
+        Outcome {
+            entries_to_process: 4,
+            entries_processed: 4,
+            entries_skipped_by_entry_flags: 2,
+            symlink_metadata_calls: 2,
+            ..Default::default()
+        },
+        "entries the sparse checkout excludes are treated like skip-worktree entries, even without the flag"
+    );
+}
+
 #[test]
 fn subomdule_nochange() {
     assert_eq!(
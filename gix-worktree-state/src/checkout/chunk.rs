@@ -83,6 +83,7 @@ pub struct Options {
     pub fs: gix_fs::Capabilities,
     pub destination_is_initially_empty: bool,
     pub overwrite_existing: bool,
+    pub skip_identical_files: bool,
     pub keep_going: bool,
     pub filter_process_delay: gix_filter::driver::apply::Delay,
 }
@@ -93,6 +94,7 @@ impl From<&checkout::Options> for Options {
             fs: opts.fs,
             destination_is_initially_empty: opts.destination_is_initially_empty,
             overwrite_existing: opts.overwrite_existing,
+            skip_identical_files: opts.skip_identical_files,
             keep_going: opts.keep_going,
             filter_process_delay: opts.filter_process_delay,
         }
@@ -1,6 +1,6 @@
 use std::{
     fs::OpenOptions,
-    io::Write,
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 
@@ -70,6 +70,7 @@ pub fn checkout<'entry, Find>(
         },
         destination_is_initially_empty,
         overwrite_existing,
+        skip_identical_files,
         filter_process_delay,
         ..
     }: crate::checkout::chunk::Options,
@@ -103,6 +104,16 @@ where
             )?;
             let (num_bytes, file, set_executable_after_creation) = match filtered {
                 ToWorktreeOutcome::Unchanged(buf) | ToWorktreeOutcome::Buffer(buf) => {
+                    if skip_identical_files && !destination_is_initially_empty {
+                        if let Some(file) = open_if_identical(dest, buf)? {
+                            if executable_bit {
+                                set_executable_bit(&file, entry.mode == gix_index::entry::Mode::FILE_EXECUTABLE)?;
+                            }
+                            entry.stat = Stat::from_fs(&gix_index::fs::Metadata::from_file(&file)?)?;
+                            file.close()?;
+                            return Ok(Outcome::Written { bytes: 0 });
+                        }
+                    }
                     let (mut file, flag) = open_file(
                         dest,
                         destination_is_initially_empty,
@@ -268,6 +279,37 @@ pub(crate) fn open_file(
     try_op_or_unlink(path, overwrite_existing, |p| options.open(p)).map(|f| (f, set_executable_after_creation))
 }
 
+/// Open the file at `path` if it is a regular file with exactly `content`, or return `None` if it has to be written.
+fn open_if_identical(path: &Path, content: &[u8]) -> std::io::Result<Option<std::fs::File>> {
+    let Ok(mut file) = gix_features::fs::open_options_no_follow().read(true).open(path) else {
+        return Ok(None);
+    };
+    let meta = file.metadata()?;
+    if !meta.is_file() || meta.len() != content.len() as u64 {
+        return Ok(None);
+    }
+    let mut existing = Vec::with_capacity(content.len());
+    file.read_to_end(&mut existing)?;
+    Ok((existing == content).then_some(file))
+}
+
+/// Set or clear the executable bits of `file` to match `executable`, without touching its content or modification time.
+#[cfg_attr(windows, allow(unused_variables))]
+fn set_executable_bit(file: &std::fs::File, executable: bool) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perm = file.metadata()?.permissions();
+        let mode = perm.mode();
+        let desired_mode = if executable { mode | 0o111 } else { mode & !0o111 };
+        if desired_mode != mode {
+            perm.set_mode(desired_mode);
+            file.set_permissions(perm)?;
+        }
+    }
+    Ok(())
+}
+
 /// Close `file` and store its stats in `entry`, possibly setting `file` executable depending on `set_executable_after_creation`.
 #[cfg_attr(windows, allow(unused_variables))]
 pub(crate) fn finalize_entry(
@@ -51,9 +51,18 @@ pub struct Options {
     pub destination_is_initially_empty: bool,
     /// If true, default false, worktree entries on disk will be overwritten with content from the index
     /// even if they appear to be changed. When creating directories that clash with existing worktree entries,
-    /// these will try to delete the existing entry.
+    /// these will try to delete the existing entry, as will symlinks and empty directories in place of files,
+    /// at the cost of an additional `lstat` call for each file.
     /// This is similar in behaviour as `git checkout --force`.
     pub overwrite_existing: bool,
+    /// If true, default false, files that already exist on disk with exactly the content that would be written are left
+    /// untouched to keep their modification time intact, which helps stat-based status checks.
+    /// Only their executable bit is adjusted if it doesn't match the entry, without rewriting their content.
+    ///
+    /// This requires reading each existing file of the same size to compare it, and it only applies to content that is
+    /// available in memory, so the output of long-running filter processes is always written.
+    /// It has no effect if `destination_is_initially_empty` is true.
+    pub skip_identical_files: bool,
     /// If true, default false, try to checkout as much as possible and don't abort on first error which isn't
     /// due to a conflict.
     /// The checkout operation will never fail, but count the encountered errors instead along with their paths.
@@ -0,0 +1,9 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+echo -n "content" > file.txt
+
+git add -A
+git commit -q -m "init"
@@ -245,6 +245,56 @@ fn overwriting_files_and_lone_directories_works() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn identical_files_keep_their_modification_time_if_requested() -> crate::Result {
+    let one_day_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(24 * 60 * 60);
+    for skip_identical_files in [false, true] {
+        let mut opts = opts_from_probe();
+        opts.destination_is_initially_empty = false;
+        opts.overwrite_existing = true;
+        opts.skip_identical_files = skip_identical_files;
+        let (_source, destination, _index, outcome) = checkout_index_in_tmp_dir_opts(
+            opts.clone(),
+            "make_mixed_without_submodules_and_symlinks",
+            |_| true,
+            |d| {
+                let write_with_old_mtime = |path: &Path, content: &[u8]| -> std::io::Result<()> {
+                    std::fs::write(path, content)?;
+                    fs::File::options().write(true).open(path)?.set_modified(one_day_ago)
+                };
+                write_with_old_mtime(&d.join("executable"), b"content")?; // same content, but not executable
+                let dir = d.join("dir").join("sub-dir");
+                std::fs::create_dir_all(&dir)?;
+                write_with_old_mtime(&dir.join("file"), b"old") // different content
+            },
+        )?;
+        assert!(outcome.errors.is_empty());
+        assert_eq!(outcome.files_updated, 5, "skipped files are still accounted for");
+
+        let executable = destination.path().join("executable");
+        let meta = fs::symlink_metadata(&executable)?;
+        assert_eq!(
+            meta.modified()? == one_day_ago,
+            skip_identical_files,
+            "identical files are only left alone if requested"
+        );
+        assert_eq!(std::fs::read(&executable)?.as_bstr(), "content");
+        #[cfg(unix)]
+        if opts.fs.executable_bit {
+            assert_ne!(meta.mode() & 0o111, 0, "the executable bit is fixed either way");
+        }
+
+        let file = destination.path().join("dir").join("sub-dir").join("file");
+        assert_ne!(
+            fs::symlink_metadata(&file)?.modified()?,
+            one_day_ago,
+            "files with different content are always written"
+        );
+        assert_eq!(std::fs::read(&file)?.as_bstr(), "even other content\n");
+    }
+    Ok(())
+}
+
 #[test]
 fn symlinks_become_files_if_disabled() -> crate::Result {
     let mut opts = opts_from_probe();
@@ -431,6 +481,52 @@ fn collisions_are_detected_on_a_case_insensitive_filesystem_even_with_delayed_fi
     }
 }
 
+#[test]
+#[cfg_attr(windows, ignore = "symlinks to files can't be created without special permissions")]
+fn case_colliding_files_and_symlinks_are_replaced_if_overwriting_is_allowed() -> crate::Result {
+    for colliding_is_symlink in [false, true] {
+        let mut opts = opts_from_probe();
+        opts.overwrite_existing = true;
+        opts.destination_is_initially_empty = false;
+        let (_source, destination, _index, outcome) = checkout_index_in_tmp_dir_opts(
+            opts.clone(),
+            "make_case_colliding_file",
+            |_| true,
+            |d| {
+                std::fs::write(d.join("target"), b"target")?;
+                if colliding_is_symlink {
+                    symlink::symlink_file(d.join("target"), d.join("File.txt"))
+                } else {
+                    std::fs::write(d.join("File.txt"), b"other")
+                }
+            },
+        )?;
+        assert!(outcome.collisions.is_empty());
+
+        let colliding = destination.path().join("File.txt");
+        if opts.fs.ignore_case {
+            assert!(
+                std::fs::symlink_metadata(&colliding)?.is_file(),
+                "symlinks are removed, files are overwritten"
+            );
+            assert_eq!(std::fs::read(&colliding)?, b"content");
+        } else {
+            assert_eq!(
+                std::fs::symlink_metadata(&colliding)?.is_symlink(),
+                colliding_is_symlink,
+                "nothing collided"
+            );
+            assert_eq!(std::fs::read(destination.path().join("file.txt"))?, b"content");
+        }
+        assert_eq!(
+            std::fs::read(destination.path().join("target"))?,
+            b"target",
+            "we never write through symlinks"
+        );
+    }
+    Ok(())
+}
+
 fn multi_threaded() -> bool {
     gix_features::parallel::num_threads(None) > 1
 }
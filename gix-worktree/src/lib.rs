@@ -52,6 +52,8 @@ pub struct Stack {
     /// A lookup table for object ids to read from in some situations when looking up attributes or exclusions.
     id_mappings: Vec<PathIdMapping>,
     statistics: stack::Statistics,
+    /// If `Some(…)`, the paths of all directories created while creating leading directories, in order of creation.
+    created_directories: Option<Vec<std::path::PathBuf>>,
 }
 
 pub(crate) type PathIdMapping = (BString, gix_hash::ObjectId);
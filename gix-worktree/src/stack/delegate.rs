@@ -27,6 +27,8 @@ pub(crate) struct StackDelegate<'a, 'find> {
     pub objects: &'find dyn gix_object::Find,
     pub case: gix_glob::pattern::Case,
     pub statistics: &'a mut super::Statistics,
+    #[cfg_attr(not(feature = "attributes"), allow(dead_code))]
+    pub created_directories: Option<&'a mut Vec<std::path::PathBuf>>,
 }
 
 impl<'a, 'find> gix_fs::stack::Delegate for StackDelegate<'a, 'find> {
@@ -111,6 +113,7 @@ impl<'a, 'find> gix_fs::stack::Delegate for StackDelegate<'a, 'find> {
                 self.is_dir,
                 &mut self.statistics.delegate.num_mkdir_calls,
                 *unlink_on_collision,
+                self.created_directories.as_deref_mut(),
             )?,
             #[cfg(feature = "attributes")]
             State::AttributesAndIgnoreStack { .. } | State::AttributesStack(_) => {}
@@ -145,13 +148,26 @@ fn create_leading_directory(
     is_dir: bool,
     mkdir_calls: &mut usize,
     unlink_on_collision: bool,
+    created_directories: Option<&mut Vec<std::path::PathBuf>>,
 ) -> std::io::Result<()> {
     if is_last_component && !is_dir {
-        return Ok(());
+        return if unlink_on_collision {
+            unlink_leaf_on_collision(stack.current())
+        } else {
+            Ok(())
+        };
     }
+    let record_creation = |created_directories: Option<&mut Vec<std::path::PathBuf>>| {
+        if let Some(created_directories) = created_directories {
+            created_directories.push(stack.current().to_owned());
+        }
+    };
     *mkdir_calls += 1;
     match std::fs::create_dir(stack.current()) {
-        Ok(()) => Ok(()),
+        Ok(()) => {
+            record_creation(created_directories);
+            Ok(())
+        }
         Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
             let meta = stack.current().symlink_metadata()?;
             if meta.is_dir() {
@@ -163,7 +179,9 @@ fn create_leading_directory(
                     std::fs::remove_file(stack.current())?;
                 }
                 *mkdir_calls += 1;
-                std::fs::create_dir(stack.current())
+                std::fs::create_dir(stack.current())?;
+                record_creation(created_directories);
+                Ok(())
             } else {
                 Err(err)
             }
@@ -171,3 +189,30 @@ fn create_leading_directory(
         Err(err) => Err(err),
     }
 }
+
+/// Make room for a file at `path` by removing a symlink or an empty directory that is in its place.
+/// Files are left for the caller to overwrite, and non-empty directories are never removed.
+#[cfg(feature = "attributes")]
+fn unlink_leaf_on_collision(path: &std::path::Path) -> std::io::Result<()> {
+    let meta = match path.symlink_metadata() {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if meta.file_type().is_symlink() {
+        gix_fs::symlink::remove(path)
+    } else if meta.is_dir() {
+        if std::fs::read_dir(path)?.next().is_some() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!(
+                    "Refusing to replace non-empty directory at '{}' with a file",
+                    path.display()
+                ),
+            ));
+        }
+        std::fs::remove_dir(path)
+    } else {
+        Ok(())
+    }
+}
@@ -27,6 +27,8 @@ pub enum State {
     #[cfg(feature = "attributes")]
     CreateDirectoryAndAttributesStack {
         /// If there is a symlink or a file in our path, try to unlink it before creating the directory.
+        /// Symlinks and empty directories in place of the final file are removed as well, while non-empty directories
+        /// cause an error. Note that this costs an additional `lstat` call for each file.
         unlink_on_collision: bool,
         /// State to handle attribute information
         attributes: state::Attributes,
@@ -46,6 +48,15 @@ pub enum State {
     IgnoreStack(state::Ignore),
 }
 
+/// The error returned if a [`Stack`] is asked for information that its [`State`] wasn't configured to provide, like
+/// attributes of a stack created with [`State::for_status()`].
+#[derive(Debug, thiserror::Error)]
+#[error("Cannot provide {needed} information as the stack wasn't configured for it")]
+pub struct StateMismatch {
+    /// The kind of information that was requested, like `exclude` or `attribute`.
+    pub needed: &'static str,
+}
+
 #[must_use]
 pub struct Platform<'a> {
     parent: &'a Stack,
@@ -74,6 +85,7 @@ impl Stack {
             buf,
             id_mappings,
             statistics: Statistics::default(),
+            created_directories: None,
         }
     }
 
@@ -123,6 +135,7 @@ impl Stack {
             objects,
             case: self.case,
             statistics: &mut self.statistics,
+            created_directories: self.created_directories.as_mut(),
         };
         self.stack
             .make_relative_path_current(relative.as_ref(), &mut delegate)?;
@@ -135,6 +148,8 @@ impl Stack {
     /// All effects are similar to [`at_path()`][Self::at_path()].
     ///
     /// If `relative` ends with `/` and `is_dir` is `None`, it is automatically assumed to be a directory.
+    /// Gitlinks of submodules should be declared as directories, as this is how `git` matches them against
+    /// exclude patterns, so `sub/` excludes the submodule at `sub`.
     ///
     /// ### Panics
     ///
@@ -154,6 +169,93 @@ impl Stack {
             objects,
         )
     }
+
+    /// Set the stack to the repo-`relative` path, reading all exclude files along the way, and return the exclude pattern
+    /// that matches it, if any. `is_dir` and `objects` are used as in [`at_entry()`][Self::at_entry()].
+    ///
+    /// The returned match provides the [source file](gix_ignore::search::Match::source) of the pattern along with its
+    /// [line number](gix_ignore::search::Match::sequence_number) to be able to tell the user why a path is ignored.
+    /// If one of the leading directories of `relative` is excluded, its pattern is returned right away, as nothing inside
+    /// of an excluded directory can be included again.
+    /// Note that the pattern might be negated, which means `relative` is included.
+    ///
+    /// If the cache was configured without exclude patterns, an error with a [`StateMismatch`] is returned.
+    ///
+    /// ### Panics
+    ///
+    /// On illformed UTF8 in `relative`.
+    pub fn matching_exclude_pattern<'r>(
+        &mut self,
+        relative: impl Into<&'r BStr>,
+        is_dir: Option<bool>,
+        objects: &dyn gix_object::Find,
+    ) -> std::io::Result<Option<gix_ignore::search::Match<'_>>> {
+        if self.state.ignore().is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                StateMismatch { needed: "exclude" },
+            ));
+        }
+        let is_dir = self.at_entry(relative, is_dir, objects)?.is_dir;
+        let relative_path = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(self.stack.current_relative()));
+        Ok(self
+            .state
+            .ignore_or_panic()
+            .matching_exclude_pattern(relative_path.as_bstr(), is_dir, self.case))
+    }
+}
+
+/// Directory traversal
+impl Stack {
+    /// Set the stack to the directory at the repo-`relative` path, just like a directory walk would when entering it,
+    /// and read the exclude and attribute files of it and its leading directories unless they are already known.
+    /// `objects` is used to lookup objects from an [id mapping][crate::stack::State::id_mappings_from_index()].
+    ///
+    /// This allows walks to read the files of directories only if they are entered, so directories that are pruned,
+    /// for example as they are excluded, never cause their files to be read.
+    /// Exclude files read from the object database are kept when a directory is [left](Self::pop_directory()), so entering
+    /// it again doesn't read them again. They are forgotten once its parent directory is left.
+    ///
+    /// ### Panics
+    ///
+    /// On illformed UTF8 in `relative`.
+    pub fn push_directory<'r>(
+        &mut self,
+        relative: impl Into<&'r BStr>,
+        objects: &dyn gix_object::Find,
+    ) -> std::io::Result<()> {
+        let relative = gix_path::from_bstr(relative.into());
+        let mut delegate = StackDelegate {
+            state: &mut self.state,
+            buf: &mut self.buf,
+            is_dir: true,
+            id_mappings: &self.id_mappings,
+            objects,
+            case: self.case,
+            statistics: &mut self.statistics,
+            created_directories: self.created_directories.as_mut(),
+        };
+        self.stack
+            .make_relative_directory_current(relative.as_ref(), &mut delegate)
+    }
+
+    /// Leave the directory that was last [entered](Self::push_directory()), or the directory of the path the stack
+    /// was last set to, which makes its parent directory current.
+    ///
+    /// Nothing happens if the stack is set to the root directory.
+    pub fn pop_directory(&mut self) {
+        let mut delegate = StackDelegate {
+            state: &mut self.state,
+            buf: &mut self.buf,
+            is_dir: true,
+            id_mappings: &self.id_mappings,
+            objects: &gix_object::find::Never,
+            case: self.case,
+            statistics: &mut self.statistics,
+            created_directories: self.created_directories.as_mut(),
+        };
+        self.stack.pop_directory(&mut delegate);
+    }
 }
 
 /// Mutation
@@ -173,6 +275,51 @@ impl Stack {
         self.case = case;
         self
     }
+
+    /// If `toggle` is `true`, record the paths of all directories that are created when leading directories are created
+    /// in [`State::CreateDirectoryAndAttributesStack`] mode, to be able to show progress or to remove them if a checkout fails.
+    /// Otherwise, stop recording and discard everything recorded so far.
+    ///
+    /// Directories that existed already aren't recorded, and recording is off by default.
+    pub fn set_track_created_directories(&mut self, toggle: bool) -> &mut Self {
+        self.created_directories = toggle.then(|| self.created_directories.take().unwrap_or_default());
+        self
+    }
+
+    /// Return the paths of all directories created since recording was enabled or since the last call, leaving
+    /// recording enabled if it was.
+    ///
+    /// It's always empty if [recording](Self::set_track_created_directories()) is disabled.
+    pub fn take_created_directories(&mut self) -> Vec<PathBuf> {
+        self.created_directories
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Read `.gitattributes` files of directories from `source` from now on, for example to read them from the worktree
+    /// once it was checked out, and forget everything learned about the directories visited so far so that their
+    /// attribute files are read again from the new source.
+    ///
+    /// Global attributes and `$GIT_DIR/info/attributes` files are preserved, as are the overrides and globals of
+    /// exclude patterns if present.
+    ///
+    /// If the cache was configured without attributes, an error with a [`StateMismatch`] is returned.
+    #[cfg(feature = "attributes")]
+    pub fn set_attributes_source(&mut self, source: state::attributes::Source) -> Result<&mut Self, StateMismatch> {
+        match &mut self.state {
+            State::AttributesStack(attributes) | State::CreateDirectoryAndAttributesStack { attributes, .. } => {
+                attributes.set_source(source);
+            }
+            State::AttributesAndIgnoreStack { attributes, ignore } => {
+                attributes.set_source(source);
+                ignore.clear_stack();
+            }
+            State::IgnoreStack(_) => return Err(StateMismatch { needed: "attribute" }),
+        }
+        self.stack = gix_fs::Stack::new(self.stack.root().to_owned());
+        Ok(self)
+    }
 }
 
 /// Access
@@ -181,6 +328,11 @@ impl Stack {
     pub fn statistics(&self) -> &Statistics {
         &self.statistics
     }
+    /// Return the paths of all directories created since [recording](Self::set_track_created_directories()) was enabled,
+    /// in order of creation.
+    pub fn created_directories(&self) -> &[PathBuf] {
+        self.created_directories.as_deref().unwrap_or_default()
+    }
     /// Return the state for introspection.
     pub fn state(&self) -> &State {
         &self.state
@@ -2,7 +2,7 @@ use std::path::Path;
 
 use bstr::ByteSlice;
 
-use crate::stack::Platform;
+use crate::stack::{Platform, StateMismatch};
 
 /// Access
 impl<'a> Platform<'a> {
@@ -38,15 +38,24 @@ impl<'a> Platform<'a> {
     /// Check all exclude patterns to see if the currently set path matches any of them.
     ///
     /// Note that this pattern might be negated, and means this path in included.
+    /// The match also provides the file the pattern was read from along with its 1-based line number, similar to
+    /// `git check-ignore -v`.
     ///
     /// # Panics
     ///
     /// If the cache was configured without exclude patterns.
     pub fn matching_exclude_pattern(&self) -> Option<gix_ignore::search::Match<'_>> {
-        let ignore = self.parent.state.ignore_or_panic();
+        self.try_matching_exclude_pattern()
+            .expect("BUG: must not try to check excludes without it being setup")
+    }
+
+    /// Like [`matching_exclude_pattern()`](Self::matching_exclude_pattern()), but fails if the cache was configured without
+    /// exclude patterns.
+    pub fn try_matching_exclude_pattern(&self) -> Result<Option<gix_ignore::search::Match<'_>>, StateMismatch> {
+        let ignore = self.parent.state.ignore().ok_or(StateMismatch { needed: "exclude" })?;
         let relative_path =
             gix_path::to_unix_separators_on_windows(gix_path::into_bstr(self.parent.stack.current_relative()));
-        ignore.matching_exclude_pattern(relative_path.as_bstr(), self.is_dir, self.parent.case)
+        Ok(ignore.matching_exclude_pattern(relative_path.as_bstr(), self.is_dir, self.parent.case))
     }
 
     /// Match all attributes at the current path and store the result in `out`, returning `true` if at least one attribute was found.
@@ -56,10 +65,40 @@ impl<'a> Platform<'a> {
     /// If the cache was configured without attributes.
     #[cfg(feature = "attributes")]
     pub fn matching_attributes(&self, out: &mut gix_attributes::search::Outcome) -> bool {
-        let attrs = self.parent.state.attributes_or_panic();
+        self.try_matching_attributes(out)
+            .expect("BUG: must not try to check attributes without it being setup")
+    }
+
+    /// Like [`matching_attributes()`](Self::matching_attributes()), but fails if the cache was configured without attributes.
+    #[cfg(feature = "attributes")]
+    pub fn try_matching_attributes(&self, out: &mut gix_attributes::search::Outcome) -> Result<bool, StateMismatch> {
+        let attrs = self
+            .parent
+            .state
+            .attributes()
+            .ok_or(StateMismatch { needed: "attribute" })?;
         let relative_path =
             gix_path::to_unix_separators_on_windows(gix_path::into_bstr(self.parent.stack.current_relative()));
-        attrs.matching_attributes(relative_path.as_bstr(), self.parent.case, self.is_dir, out)
+        Ok(attrs.matching_attributes(relative_path.as_bstr(), self.parent.case, self.is_dir, out))
+    }
+
+    /// Return the paths of all attribute files that are consulted when [matching attributes](Self::matching_attributes())
+    /// of this path, in order of ascending precedence, or fail if the cache was configured without attributes.
+    ///
+    /// Together with the [source and line number](gix_attributes::search::Match::location) of each match in the outcome
+    /// of a lookup, this explains why a path has certain attributes, similar to what `git check-attr` can do.
+    /// To learn about each pattern that applied, enable [tracing](gix_attributes::search::Outcome::set_trace()) on the
+    /// outcome before the lookup.
+    /// Files without patterns aren't listed, files read from the index are named by their path in the index, and the
+    /// built-in macros are listed as `[builtin]`.
+    #[cfg(feature = "attributes")]
+    pub fn attribute_sources(&self) -> Result<Vec<&Path>, StateMismatch> {
+        let attrs = self
+            .parent
+            .state
+            .attributes()
+            .ok_or(StateMismatch { needed: "attribute" })?;
+        Ok(attrs.sources().collect())
     }
 }
 
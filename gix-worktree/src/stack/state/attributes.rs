@@ -9,6 +9,12 @@ use crate::{
     PathIdMapping, Stack,
 };
 
+/// The path of [id mappings](crate::stack::State::id_mappings_from_index()) that refer to attribute files which are
+/// loaded as globals, as added by [`State::add_global_attributes_to_id_mappings()`](crate::stack::State::add_global_attributes_to_id_mappings()).
+///
+/// It's an absolute path, which can't be the path of an entry in the index.
+pub const GLOBALS_ID_MAPPING_PATH: &str = "/";
+
 /// Various aggregate numbers related [`Attributes`].
 #[derive(Default, Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -29,6 +35,8 @@ pub struct Statistics {
 /// These mappings are typically produced from an index.
 /// If a tree should be the source, build an attribute list from a tree instead, or convert a tree to an index.
 ///
+/// The variants that use both sources decide separately for each `.gitattributes` file, so the preferred source
+/// shadows the other one only for the directories it has a file for.
 #[derive(Default, Debug, Clone, Copy)]
 pub enum Source {
     /// Use this when no worktree checkout is available, like in bare repositories, during clones, or when accessing blobs from
@@ -41,6 +49,9 @@ pub enum Source {
     IdMappingThenWorktree,
     /// Read from the worktree and if not present, read them from the id mappings.
     ///
+    /// This matches how `git` resolves attributes in a checked-out repository, as modified `.gitattributes` files
+    /// take effect right away while deleted ones still apply through the index.
+    ///
     /// This is typically used when *checking in* files, and it's possible for sparse worktrees not to have a `.gitattribute` file
     /// checked out even though it's available in the index.
     WorktreeThenIdMapping,
@@ -57,29 +68,136 @@ impl Source {
     }
 }
 
+/// A snapshot of the lookup table of attribute and macro names of [`Attributes`], along with the id mappings of the attribute
+/// files it was built from, for persisting the table and passing it to [`Attributes::new()`] in another process.
+///
+/// Obtain it with [`Attributes::collection_snapshot()`], and check if it can still be used with [`is_valid_for()`](Self::is_valid_for()).
+/// Note that attribute files read from the worktree or from paths aren't tracked.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CollectionSnapshot {
+    /// The lookup table of attribute and macro names.
+    pub collection: gix_attributes::search::MetadataCollection,
+    /// The paths and object ids of all attribute files among the id mappings at the time the snapshot was taken.
+    pub id_mappings: Vec<PathIdMapping>,
+}
+
+impl CollectionSnapshot {
+    /// Return `true` if the attribute files among `id_mappings`, as obtained by
+    /// [`State::id_mappings_from_index()`](crate::stack::State::id_mappings_from_index()), are the same as the ones this
+    /// snapshot was taken with, so that its collection can be used instead of building it again.
+    pub fn is_valid_for(&self, id_mappings: &[PathIdMapping]) -> bool {
+        attribute_file_mappings(id_mappings).eq(self.id_mappings.iter())
+    }
+}
+
+/// Return all mappings of `.gitattributes` files and of global attribute files.
+fn attribute_file_mappings(id_mappings: &[PathIdMapping]) -> impl Iterator<Item = &PathIdMapping> {
+    id_mappings.iter().filter(|(path, _)| {
+        path == GLOBALS_ID_MAPPING_PATH
+            || path
+                .rfind_byte(b'/')
+                .map_or(path.as_bstr(), |pos| path[pos + 1..].as_bstr())
+                .eq_ignore_ascii_case(b".gitattributes")
+    })
+}
+
 /// Initialization
 impl Attributes {
     /// Create a new instance from an attribute match group that represents `globals`. It can more easily be created with
     /// [`AttributeMatchGroup::new_globals()`].
     ///
     /// * `globals` contribute first and consist of all globally available, static files.
-    /// * `info_attributes` is a path that should refer to `.git/info/attributes`, and it's not an error if the file doesn't exist.
+    /// * `info_attributes` are paths that should refer to `.git/info/attributes` files, like the one of the main repository
+    ///   followed by the one of a linked worktree, and it's not an error if they don't exist. They are loaded in order, so later
+    ///   files take precedence, and paths that were seen before are ignored.
     /// * `case` is used to control case-sensitivity during matching.
     /// * `source` specifies from where the directory-based attribute files should be loaded from.
     pub fn new(
         globals: AttributeMatchGroup,
-        info_attributes: Option<PathBuf>,
+        info_attributes: impl IntoIterator<Item = PathBuf>,
         source: Source,
         collection: gix_attributes::search::MetadataCollection,
     ) -> Self {
+        let mut unique_info_attributes = Vec::new();
+        for path in info_attributes {
+            if !unique_info_attributes.contains(&path) {
+                unique_info_attributes.push(path);
+            }
+        }
         Attributes {
             globals,
             stack: Default::default(),
-            info_attributes,
+            info_attributes: unique_info_attributes,
+            info_attributes_loaded: false,
+            id_mapping_globals_loaded: false,
             source,
             collection,
         }
     }
+
+    /// Load the attributes file at `path`, like the one configured in `core.attributesFile` or the system-wide one, into
+    /// our `globals`. It's not an error if the file doesn't exist.
+    ///
+    /// Globals have the lowest precedence, so patterns of `$GIT_DIR/info/attributes` and all `.gitattributes` files
+    /// override them. Among globals, later files win, so the system-wide file should be loaded before the user's one.
+    /// As with all patterns, the case-sensitivity is determined by the [`Stack`] at match time.
+    pub fn with_global_file(mut self, path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let mut buf = Vec::new();
+        self.globals.add_patterns_file(
+            path.into(),
+            true,
+            None,
+            &mut buf,
+            &mut self.collection,
+            true, /* read macros */
+        )?;
+        Ok(self)
+    }
+}
+
+/// Mutation
+impl Attributes {
+    /// Remove all patterns that were read from `.gitattributes` files while walking directories, along with those of the
+    /// `$GIT_DIR/info/attributes` files which will be read again when the root is pushed next, while keeping all globals.
+    ///
+    /// This allows to reuse this instance with a new [`Stack`] for a walk that is unrelated to the previous one,
+    /// without reading global attribute files again.
+    pub fn clear_stack(&mut self) {
+        self.stack = Default::default();
+        self.info_attributes_loaded = false;
+    }
+
+    /// Read `.gitattributes` files of directories from `source` from now on, and [clear the stack](Self::clear_stack()) to
+    /// forget all patterns read from the previous source.
+    ///
+    /// Globals are preserved, and the `$GIT_DIR/info/attributes` files are read again so they keep applying.
+    /// Use [`Stack::set_attributes_source()`] to change the source of a stack that is in use.
+    pub fn set_source(&mut self, source: Source) -> &mut Self {
+        self.source = source;
+        self.clear_stack();
+        self
+    }
+}
+
+/// Access
+impl Attributes {
+    /// Return the source from which `.gitattributes` files of directories are read.
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Take a snapshot of the lookup table of attribute and macro names built so far, along with the attribute files among
+    /// `id_mappings` it was built from, to persist it and avoid rebuilding it later as long as these files don't change.
+    ///
+    /// `id_mappings` should be the ones the [`Stack`] was created with, and the snapshot should be taken once all attribute files
+    /// of interest were read.
+    pub fn collection_snapshot(&self, id_mappings: &[PathIdMapping]) -> CollectionSnapshot {
+        CollectionSnapshot {
+            collection: self.collection.clone(),
+            id_mappings: attribute_file_mappings(id_mappings).cloned().collect(),
+        }
+    }
 }
 
 impl Attributes {
@@ -166,29 +284,58 @@ impl Attributes {
         }
 
         // Need one stack level per component so push and pop matches, but only if this isn't the root level which is never popped.
-        if !added && self.info_attributes.is_none() {
+        if !added && (self.info_attributes_loaded || self.info_attributes.is_empty()) {
             self.stack
                 .add_patterns_buffer(&[], "<empty dummy>".into(), None, &mut self.collection, true)
         }
 
-        // When reading the root, always the first call, we can try to also read the `.git/info/attributes` file which is
-        // by nature never popped, and follows the root, as global.
-        if let Some(info_attr) = self.info_attributes.take() {
-            let added = self.stack.add_patterns_file(
-                info_attr,
-                true,
-                None,
-                buf,
-                &mut self.collection,
-                true, /* read macros */
-            )?;
-            stats.pattern_files += usize::from(added);
-            stats.tried_pattern_files += 1;
+        // Globals can be provided by id mappings as well, and are kept along with all other globals.
+        if !self.id_mapping_globals_loaded {
+            let start = id_mappings.partition_point(|t| t.0.as_bstr() < GLOBALS_ID_MAPPING_PATH);
+            for (_, id) in id_mappings[start..]
+                .iter()
+                .take_while(|t| t.0.as_bstr() == GLOBALS_ID_MAPPING_PATH)
+            {
+                let blob = objects
+                    .find_blob(id, buf)
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+                self.globals.add_patterns_buffer(
+                    blob.data,
+                    format!("<global attributes {id}>").into(),
+                    None,
+                    &mut self.collection,
+                    true, /* read macros */
+                );
+                stats.patterns_buffers += 1;
+            }
+            self.id_mapping_globals_loaded = true;
+        }
+
+        // When reading the root, always the first call, we can try to also read the `.git/info/attributes` files which are
+        // by nature never popped, and follow the root, as global.
+        if !self.info_attributes_loaded {
+            for info_attr in &self.info_attributes {
+                let added = self.stack.add_patterns_file(
+                    info_attr.clone(),
+                    true,
+                    None,
+                    buf,
+                    &mut self.collection,
+                    true, /* read macros */
+                )?;
+                stats.pattern_files += usize::from(added);
+                stats.tried_pattern_files += 1;
+            }
+            self.info_attributes_loaded = true;
         }
 
         Ok(())
     }
 
+    pub(crate) fn sources(&self) -> impl Iterator<Item = &Path> + '_ {
+        self.globals.sources().chain(self.stack.sources())
+    }
+
     pub(crate) fn matching_attributes(
         &self,
         relative_path: &BStr,
@@ -248,4 +395,40 @@ impl Stack {
     pub fn attributes_collection(&self) -> &gix_attributes::search::MetadataCollection {
         &self.state.attributes_or_panic().collection
     }
+
+    /// Match the attributes of all repo-`relative_paths` and return one outcome per path, in the order of `relative_paths`.
+    /// Each outcome starts out as a copy of `template`, as obtained by [`attribute_matches()`][Stack::attribute_matches()]
+    /// or [`selected_attribute_matches()`][Stack::selected_attribute_matches()].
+    /// `objects` is used as in [`at_entry()`][Stack::at_entry()], and paths ending in `/` are assumed to be directories.
+    ///
+    /// Paths are visited in sorted order so that the attribute files of leading directories they share are only read once,
+    /// which saves work compared to calling [`at_entry()`][Stack::at_entry()] for each path unless these are sorted already.
+    ///
+    /// If the cache was configured without attributes, an error with a [`StateMismatch`](crate::stack::StateMismatch) is returned.
+    ///
+    /// ### Panics
+    ///
+    /// On illformed UTF8 in any of `relative_paths`.
+    pub fn matching_attributes_for_entries(
+        &mut self,
+        relative_paths: &[&BStr],
+        template: &gix_attributes::search::Outcome,
+        objects: &dyn gix_object::Find,
+    ) -> std::io::Result<Vec<gix_attributes::search::Outcome>> {
+        if self.state.attributes().is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                crate::stack::StateMismatch { needed: "attribute" },
+            ));
+        }
+        let mut order: Vec<_> = (0..relative_paths.len()).collect();
+        order.sort_by_key(|idx| relative_paths[*idx]);
+
+        let mut outcomes = vec![template.clone(); relative_paths.len()];
+        for idx in order {
+            self.at_entry(relative_paths[idx], None, objects)?
+                .matching_attributes(&mut outcomes[idx]);
+        }
+        Ok(outcomes)
+    }
 }
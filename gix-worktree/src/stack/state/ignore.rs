@@ -37,6 +37,10 @@ impl Source {
     }
 }
 
+/// A function returning `true` if the given repository-relative path is part of the sparse checkout,
+/// and `false` if it is excluded from the worktree.
+pub type InSparseCheckout = std::sync::Arc<dyn Fn(&BStr) -> bool + Send + Sync>;
+
 /// Various aggregate numbers related [`Ignore`].
 #[derive(Default, Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -47,6 +51,14 @@ pub struct Statistics {
     pub pattern_files: usize,
     /// Amount of pattern files we tried to find on disk.
     pub tried_pattern_files: usize,
+    /// Amount of patterns buffers that didn't have to be read from the index again as they were read before.
+    pub cached_patterns_buffers: usize,
+}
+
+impl Default for Ignore {
+    fn default() -> Self {
+        Ignore::new(Default::default(), Default::default(), None, Default::default())
+    }
 }
 
 impl Ignore {
@@ -54,7 +66,9 @@ impl Ignore {
     /// stack be dynamic.
     ///
     /// The `exclude_file_name_for_directories` is an optional override for the filename to use when checking per-directory
-    /// ignore files within the repository, defaults to`.gitignore`.
+    /// ignore files within the repository, defaults to`.gitignore`. It's used when reading them from the worktree as well as
+    /// by [`id_mappings_from_index()`](crate::stack::State::id_mappings_from_index()), which allows to evaluate files
+    /// like `.dockerignore` with the same machinery.
     pub fn new(
         overrides: IgnoreMatchGroup,
         globals: IgnoreMatchGroup,
@@ -69,14 +83,47 @@ impl Ignore {
             exclude_file_name_for_directories: exclude_file_name_for_directories
                 .map_or_else(|| ".gitignore".into(), ToOwned::to_owned),
             source,
+            directories: Vec::with_capacity(6),
+            patterns_from_id_mappings: Default::default(),
+            in_sparse_checkout: None,
         }
     }
+
+    /// Return the name of the per-directory ignore files, like `.gitignore`.
+    pub fn exclude_file_name_for_directories(&self) -> &BStr {
+        self.exclude_file_name_for_directories.as_bstr()
+    }
+
+    /// Return `true` if `relative_path` is part of the sparse checkout, which is always the case if no sparse checkout
+    /// was configured with [`State::for_status()`](crate::stack::State::for_status()).
+    pub fn is_in_sparse_checkout(&self, relative_path: &BStr) -> bool {
+        self.in_sparse_checkout
+            .as_ref()
+            .map_or(true, |in_sparse_checkout| in_sparse_checkout(relative_path))
+    }
 }
 
 impl Ignore {
+    /// Forget all patterns that were read from the ignore files of directories, keeping overrides and globals.
+    #[cfg(feature = "attributes")]
+    pub(crate) fn clear_stack(&mut self) {
+        self.stack = Default::default();
+        self.matched_directory_patterns_stack.clear();
+        self.directories.clear();
+        self.patterns_from_id_mappings.clear();
+    }
+
     pub(crate) fn pop_directory(&mut self) {
         self.matched_directory_patterns_stack.pop().expect("something to pop");
-        self.stack.patterns.pop().expect("something to pop");
+        let patterns = self.stack.patterns.pop().expect("something to pop");
+        let (rela_dir, id_mapping) = self.directories.pop().expect("something to pop");
+
+        // Subdirectories can only be entered again through this directory, so forget them to keep the cache bounded.
+        self.patterns_from_id_mappings
+            .retain(|path, _| !is_in_subdirectory(path.as_bstr(), rela_dir.as_bstr()));
+        if let Some((path, id)) = id_mapping {
+            self.patterns_from_id_mappings.insert(path, (id, patterns));
+        }
     }
     /// The match groups from lowest priority to highest.
     pub(crate) fn match_groups(&self) -> [&IgnoreMatchGroup; 3] {
@@ -90,25 +137,28 @@ impl Ignore {
         case: Case,
     ) -> Option<gix_ignore::search::Match<'_>> {
         let groups = self.match_groups();
+        let to_match = |(gidx, plidx, pidx): (usize, usize, usize)| {
+            let list = &groups[gidx].patterns[plidx];
+            let mapping = &list.patterns[pidx];
+            gix_ignore::search::Match {
+                pattern: &mapping.pattern,
+                sequence_number: mapping.sequence_number,
+                kind: mapping.value,
+                source: list.source.as_deref(),
+            }
+        };
         let mut dir_match = None;
-        if let Some((source, mapping)) = self
+        let mut dir_matches = self
             .matched_directory_patterns_stack
             .iter()
             .rev()
-            .filter_map(|v| *v)
-            .map(|(gidx, plidx, pidx)| {
-                let list = &groups[gidx].patterns[plidx];
-                (list.source.as_deref(), &list.patterns[pidx])
-            })
-            .next()
-        {
-            let match_ = gix_ignore::search::Match {
-                pattern: &mapping.pattern,
-                sequence_number: mapping.sequence_number,
-                kind: mapping.value,
-                source,
-            };
-            if mapping.pattern.is_negative() {
+            .filter_map(|v| v.map(to_match));
+        if let Some(match_) = dir_matches.next() {
+            if match_.pattern.is_negative() {
+                // A directory can't be re-included if one of its parent directories is excluded, and neither can anything in it.
+                if let Some(excluded_parent) = dir_matches.find(|m| !m.pattern.is_negative()) {
+                    return excluded_parent.into();
+                }
                 dir_match = Some(match_);
             } else {
                 // Note that returning here is wrong if this pattern _was_ preceded by a negative pattern that
@@ -127,6 +177,20 @@ impl Ignore {
             .or(dir_match)
     }
 
+    /// Like `matching_exclude_pattern()`, but only matches `relative_path` against the patterns we currently hold,
+    /// without considering whether the directory the stack is currently set to is excluded.
+    pub(crate) fn matching_exclude_pattern_by_path(
+        &self,
+        relative_path: &BStr,
+        is_dir: Option<bool>,
+        case: Case,
+    ) -> Option<gix_ignore::search::Match<'_>> {
+        self.match_groups()
+            .iter()
+            .rev()
+            .find_map(|group| group.pattern_matching_relative_path(relative_path, is_dir, case))
+    }
+
     /// Like `matching_exclude_pattern()` but without checking if the current directory is excluded.
     /// It returns a triple-index into our data structure from which a match can be reconstructed.
     pub(crate) fn matching_exclude_pattern_no_dir(
@@ -171,32 +235,24 @@ impl Ignore {
     ) -> std::io::Result<()> {
         self.matched_directory_patterns_stack
             .push(self.matching_exclude_pattern_no_dir(rela_dir, Some(true), case));
+        self.directories.push((rela_dir.to_owned(), None));
 
-        let ignore_path_relative = gix_path::join_bstr_unix_pathsep(rela_dir, ".gitignore");
+        let ignore_path_relative =
+            gix_path::join_bstr_unix_pathsep(rela_dir, self.exclude_file_name_for_directories.as_bstr());
         let ignore_file_in_index = id_mappings.binary_search_by(|t| t.0.as_bstr().cmp(ignore_path_relative.as_ref()));
         match self.source {
-            Source::IdMapping => {
-                match ignore_file_in_index {
-                    Ok(idx) => {
-                        let ignore_blob = objects
-                            .find_blob(&id_mappings[idx].1, buf)
-                            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
-                        let ignore_path = gix_path::from_bstring(ignore_path_relative.into_owned());
-                        self.stack
-                            .add_patterns_buffer(ignore_blob.data, ignore_path, Some(Path::new("")));
-                        stats.patterns_buffers += 1;
-                    }
-                    Err(_) => {
-                        // Need one stack level per component so push and pop matches.
-                        self.stack.patterns.push(Default::default())
-                    }
+            Source::IdMapping => match ignore_file_in_index {
+                Ok(idx) => self.push_patterns_from_id_mapping(&id_mappings[idx], buf, objects, stats)?,
+                Err(_) => {
+                    // Need one stack level per component so push and pop matches.
+                    self.stack.patterns.push(Default::default())
                 }
-            }
+            },
             Source::WorktreeThenIdMappingIfNotSkipped => {
                 let follow_symlinks = ignore_file_in_index.is_err();
                 let added = gix_glob::search::add_patterns_file(
                     &mut self.stack.patterns,
-                    dir.join(".gitignore"),
+                    dir.join(gix_path::from_bstr(self.exclude_file_name_for_directories.as_bstr())),
                     follow_symlinks,
                     Some(root),
                     buf,
@@ -205,15 +261,7 @@ impl Ignore {
                 stats.tried_pattern_files += 1;
                 if !added {
                     match ignore_file_in_index {
-                        Ok(idx) => {
-                            let ignore_blob = objects
-                                .find_blob(&id_mappings[idx].1, buf)
-                                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
-                            let ignore_path = gix_path::from_bstring(ignore_path_relative.into_owned());
-                            self.stack
-                                .add_patterns_buffer(ignore_blob.data, ignore_path, Some(Path::new("")));
-                            stats.patterns_buffers += 1;
-                        }
+                        Ok(idx) => self.push_patterns_from_id_mapping(&id_mappings[idx], buf, objects, stats)?,
                         Err(_) => {
                             // Need one stack level per component so push and pop matches.
                             self.stack.patterns.push(Default::default())
@@ -224,4 +272,52 @@ impl Ignore {
         }
         Ok(())
     }
+
+    /// Push the patterns of the ignore file at `path`, reading its blob `id` from `objects` only if it wasn't read before
+    /// and is still cached.
+    fn push_patterns_from_id_mapping(
+        &mut self,
+        (path, id): &PathIdMapping,
+        buf: &mut Vec<u8>,
+        objects: &dyn gix_object::Find,
+        stats: &mut Statistics,
+    ) -> std::io::Result<()> {
+        let patterns = match self
+            .patterns_from_id_mappings
+            .remove(path)
+            .filter(|(cached_id, _)| cached_id == id)
+        {
+            Some((_, patterns)) => {
+                stats.cached_patterns_buffers += 1;
+                patterns
+            }
+            None => {
+                let ignore_blob = objects
+                    .find_blob(id, buf)
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+                stats.patterns_buffers += 1;
+                gix_glob::search::pattern::List::from_bytes(
+                    ignore_blob.data,
+                    gix_path::from_bstring(path.clone()),
+                    Some(Path::new("")),
+                )
+            }
+        };
+        self.stack.patterns.push(patterns);
+        self.directories.last_mut().expect("pushed before").1 = Some((path.clone(), *id));
+        Ok(())
+    }
+}
+
+/// Return `true` if `path` is in a subdirectory of `rela_dir`, with an empty `rela_dir` being the root.
+fn is_in_subdirectory(path: &BStr, rela_dir: &BStr) -> bool {
+    let path_in_dir = if rela_dir.is_empty() {
+        path
+    } else {
+        match path.strip_prefix(rela_dir.as_bytes()) {
+            Some(rest) if rest.first() == Some(&b'/') => rest[1..].as_bstr(),
+            _ => return false,
+        }
+    };
+    path_in_dir.contains(&b'/')
 }
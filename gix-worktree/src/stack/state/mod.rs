@@ -1,5 +1,8 @@
-use bstr::{BString, ByteSlice};
+use std::{borrow::Cow, collections::HashMap};
+
+use bstr::{BStr, BString, ByteSlice};
 use gix_glob::pattern::Case;
+use gix_object::FindExt;
 
 use crate::{stack::State, PathIdMapping};
 
@@ -15,12 +18,16 @@ pub struct Attributes {
     globals: AttributeMatchGroup,
     /// Attribute patterns that match the currently set directory (in the stack).
     ///
-    /// Note that the root-level file is always loaded, if present, followed by, the `$GIT_DIR/info/attributes`, if present, based
-    /// on the location of the `info_attributes` file.
+    /// Note that the root-level file is always loaded, if present, followed by, the `$GIT_DIR/info/attributes` files, if present,
+    /// based on the location of the `info_attributes` files.
     stack: AttributeMatchGroup,
-    /// The first time we push the root, we have to load additional information from this file if it exists along with the root attributes
-    /// file if possible, and keep them there throughout.
-    info_attributes: Option<std::path::PathBuf>,
+    /// The first time we push the root, we have to load additional information from these files if they exist along with the root attributes
+    /// file if possible, and keep them there throughout. They are loaded in order, so later files take precedence.
+    info_attributes: Vec<std::path::PathBuf>,
+    /// If `true`, the `info_attributes` files were loaded into the `stack` already.
+    info_attributes_loaded: bool,
+    /// If `true`, the global attribute files in the id mappings, if any, were loaded into `globals` already.
+    id_mapping_globals_loaded: bool,
     /// A lookup table to accelerate searches.
     collection: gix_attributes::search::MetadataCollection,
     /// Where to read `.gitattributes` data from.
@@ -29,7 +36,11 @@ pub struct Attributes {
 
 /// State related to the exclusion of files, supporting static overrides and globals, along with a stack of dynamically read
 /// ignore files from disk or from the index each time the directory changes.
-#[derive(Default, Clone)]
+/// Patterns read from the index are kept when their directory is left, so directories that are entered again don't need
+/// to be read again, until their parent directory is left as well.
+///
+/// Its default reads `.gitignore` files from the index, without any overrides or globals.
+#[derive(Clone)]
 #[allow(unused)]
 pub struct Ignore {
     /// Ignore patterns passed as overrides to everything else, typically passed on the command-line and the first patterns to
@@ -47,6 +58,23 @@ pub struct Ignore {
     pub(crate) exclude_file_name_for_directories: BString,
     /// Where to read ignore files from
     source: ignore::Source,
+    /// For each directory on the `stack`, its repository-relative path along with the id mapping its patterns were read
+    /// from, if they were read from the object database.
+    directories: Vec<(BString, Option<PathIdMapping>)>,
+    /// Patterns read from ignore files in the object database of directories that were left, keyed by their
+    /// repository-relative path, along with the id of the blob they were read from, so they don't have to be read again
+    /// when the directory is visited again.
+    /// Patterns of directories on the `stack` are moved there, and those of subdirectories are dropped when their parent
+    /// directory is left.
+    patterns_from_id_mappings: HashMap<
+        BString,
+        (
+            gix_hash::ObjectId,
+            gix_glob::search::pattern::List<gix_ignore::search::Ignore>,
+        ),
+    >,
+    /// If set, tells if a path is part of the sparse checkout, which is configured with [`State::for_status()`].
+    in_sparse_checkout: Option<ignore::InSparseCheckout>,
 }
 
 ///
@@ -72,6 +100,16 @@ impl State {
     pub fn for_add(attributes: Attributes, ignore: Ignore) -> Self {
         State::AttributesAndIgnoreStack { attributes, ignore }
     }
+
+    /// Configure a state for obtaining the status of a worktree, which only needs access to ignore files to classify
+    /// untracked files.
+    ///
+    /// If `in_sparse_checkout` is set, paths it rejects are treated as excluded from the worktree, just like entries
+    /// with the skip-worktree bit, so their *ignore* files are never used and they are never reported as removed.
+    pub fn for_status(mut ignore: Ignore, in_sparse_checkout: Option<ignore::InSparseCheckout>) -> Self {
+        ignore.in_sparse_checkout = in_sparse_checkout;
+        State::IgnoreStack(ignore)
+    }
 }
 
 /// Utilities
@@ -83,63 +121,77 @@ impl State {
     /// Note that this method…
     /// - ignores entries which aren't blobs.
     /// - ignores ignore entries which are not skip-worktree.
+    /// - ignores entries outside of the sparse checkout, if one was configured with [`for_status()`](Self::for_status()).
     /// - within merges, picks 'our' stage both for *ignore* and *attribute* files.
+    /// - returns at most one mapping per path, preferring stage 0 over stage 2, sorted by path.
+    ///
+    /// Gitlinks of submodules are never used, even if they are named like *ignore* or *attribute* files, but files next to
+    /// them are collected as usual. As the index doesn't contain the files of submodules, their *ignore* and *attribute*
+    /// files are never part of the result, which makes submodules opaque, just like in `git`.
     ///
     /// * `index` is where we look for suitable files by path in order to obtain their blob hash.
     /// * `paths` is the indices storage backend for paths.
     /// * `case` determines if the search for files should be case-sensitive or not.
+    ///
+    /// Attribute files outside of the index, like the one in `core.attributesFile`, can be added with
+    /// [`add_global_attributes_to_id_mappings()`](Self::add_global_attributes_to_id_mappings()).
     pub fn id_mappings_from_index(
         &self,
         index: &gix_index::State,
         paths: &gix_index::PathStorageRef,
         case: Case,
     ) -> Vec<PathIdMapping> {
-        let a1_backing;
-        #[cfg(feature = "attributes")]
-        let a2_backing;
-        let names = match self {
-            State::IgnoreStack(ignore) => {
-                a1_backing = [(
-                    ignore.exclude_file_name_for_directories.as_bytes().as_bstr(),
-                    Some(ignore.source),
-                )];
-                a1_backing.as_ref()
-            }
-            #[cfg(feature = "attributes")]
-            State::AttributesAndIgnoreStack { ignore, .. } => {
-                a2_backing = [
-                    (
-                        ignore.exclude_file_name_for_directories.as_bytes().as_bstr(),
-                        Some(ignore.source),
-                    ),
-                    (".gitattributes".into(), None),
-                ];
-                a2_backing.as_ref()
-            }
-            #[cfg(feature = "attributes")]
-            State::CreateDirectoryAndAttributesStack { .. } | State::AttributesStack(_) => {
-                a1_backing = [(".gitattributes".into(), None)];
-                a1_backing.as_ref()
-            }
-        };
+        self.id_mappings_from_index_with_precompose_unicode(index, paths, case, false)
+    }
 
-        index
+    /// Like [`id_mappings_from_index()`](Self::id_mappings_from_index()), but if `precompose_unicode` is `true`, paths
+    /// in the `index` are precomposed (NFC) before their basename is compared to the names of *ignore* and *attribute* files.
+    ///
+    /// This is what `core.precomposeUnicode` controls, and it assures that files which are stored decomposed (NFD) in the
+    /// index are still found. The returned paths are precomposed as well to match lookups made with precomposed paths.
+    pub fn id_mappings_from_index_with_precompose_unicode(
+        &self,
+        index: &gix_index::State,
+        paths: &gix_index::PathStorageRef,
+        case: Case,
+        precompose_unicode: bool,
+    ) -> Vec<PathIdMapping> {
+        let names = self.id_mapping_names();
+
+        fn precompose(s: &BStr, precompose_unicode: bool) -> Cow<'_, BStr> {
+            if precompose_unicode {
+                gix_utils::str::precompose_bstr(s.into())
+            } else {
+                s.into()
+            }
+        }
+        let names: Vec<_> = names
+            .into_iter()
+            .map(|(name, source)| (precompose(name, precompose_unicode), source))
+            .collect();
+        let ignore = self.ignore();
+        let mut out: Vec<_> = index
             .entries()
             .iter()
-            .filter_map(move |entry| {
-                let path = entry.path_in(paths);
+            .filter_map(|entry| {
+                let path = precompose(entry.path_in(paths), precompose_unicode);
 
                 // Stage 0 means there is no merge going on, stage 2 means it's 'our' side of the merge, but then
                 // there won't be a stage 0.
                 if entry.mode == gix_index::entry::Mode::FILE && (entry.stage() == 0 || entry.stage() == 2) {
-                    let basename = path.rfind_byte(b'/').map_or(path, |pos| path[pos + 1..].as_bstr());
+                    let basename = path
+                        .rfind_byte(b'/')
+                        .map_or(path.as_ref(), |pos| path[pos + 1..].as_bstr());
                     let ignore_source = names.iter().find_map(|t| {
                         match case {
-                            Case::Sensitive => basename == t.0,
-                            Case::Fold => basename.eq_ignore_ascii_case(t.0),
+                            Case::Sensitive => basename == t.0.as_ref(),
+                            Case::Fold => basename.eq_ignore_ascii_case(&t.0),
                         }
                         .then_some(t.1)
                     })?;
+                    if !ignore.map_or(true, |ignore| ignore.is_in_sparse_checkout(path.as_ref())) {
+                        return None;
+                    }
                     if let Some(source) = ignore_source {
                         match source {
                             ignore::Source::IdMapping => {}
@@ -151,35 +203,176 @@ impl State {
                             }
                         };
                     }
-                    Some((path.to_owned(), entry.id))
+                    Some((path.into_owned(), entry.id))
                 } else {
                     None
                 }
             })
-            .collect()
+            .collect();
+        // Precomposition may change the order, but lookups rely on these being sorted. The sort is stable, so the
+        // first of multiple entries for the same path is the one with the lowest stage, which is the one we keep.
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out.dedup_by(|a, b| a.0 == b.0);
+        out
     }
 
-    pub(crate) fn ignore_or_panic(&self) -> &Ignore {
+    /// Return the names of the files that are relevant to this state, along with the source of *ignore* files, or `None`
+    /// for *attribute* files.
+    fn id_mapping_names(&self) -> Vec<(&BStr, Option<ignore::Source>)> {
         match self {
-            State::IgnoreStack(v) => v,
+            State::IgnoreStack(ignore) => vec![(
+                ignore.exclude_file_name_for_directories.as_bytes().as_bstr(),
+                Some(ignore.source),
+            )],
             #[cfg(feature = "attributes")]
-            State::AttributesAndIgnoreStack { ignore, .. } => ignore,
+            State::AttributesAndIgnoreStack { ignore, .. } => vec![
+                (
+                    ignore.exclude_file_name_for_directories.as_bytes().as_bstr(),
+                    Some(ignore.source),
+                ),
+                (".gitattributes".into(), None),
+            ],
             #[cfg(feature = "attributes")]
-            State::AttributesStack(_) | State::CreateDirectoryAndAttributesStack { .. } => {
-                unreachable!("BUG: must not try to check excludes without it being setup")
+            State::CreateDirectoryAndAttributesStack { .. } | State::AttributesStack(_) => {
+                vec![(".gitattributes".into(), None)]
             }
         }
     }
 
+    /// Like [`id_mappings_from_index()`](Self::id_mappings_from_index()), but obtains *ignore* and *attribute* files
+    /// by traversing the tree with id `tree` in `objects`, which makes the files of any tree usable without an index.
+    ///
+    /// Nested files are found at any depth, and the returned mappings are sorted by path.
+    /// As trees don't know about entries skipped in the worktree, *ignore* files are only collected if their
+    /// source is [`IdMapping`](ignore::Source::IdMapping).
+    /// Submodules are never traversed, which keeps them opaque just like in `git`.
+    pub fn id_mappings_from_tree(
+        &self,
+        tree: &gix_hash::oid,
+        objects: &dyn gix_object::Find,
+        case: Case,
+    ) -> Result<Vec<PathIdMapping>, gix_object::find::existing_object::Error> {
+        let names = self.id_mapping_names();
+        let mut out = Vec::new();
+        let mut buf = Vec::new();
+        let mut trees = std::collections::VecDeque::from([(BString::default(), tree.to_owned())]);
+        while let Some((dir, id)) = trees.pop_front() {
+            let tree = objects.find_tree(&id, &mut buf)?;
+            for entry in tree.entries {
+                let path = || {
+                    let mut path = dir.clone();
+                    if !path.is_empty() {
+                        path.push(b'/');
+                    }
+                    path.extend_from_slice(entry.filename);
+                    path
+                };
+                if entry.mode.is_tree() {
+                    trees.push_back((path(), entry.oid.to_owned()));
+                    continue;
+                }
+                if entry.mode.kind() != gix_object::tree::EntryKind::Blob {
+                    continue;
+                }
+                let source = names.iter().find_map(|(name, source)| {
+                    match case {
+                        Case::Sensitive => entry.filename == *name,
+                        Case::Fold => entry.filename.eq_ignore_ascii_case(name),
+                    }
+                    .then_some(*source)
+                });
+                if let Some(None | Some(ignore::Source::IdMapping)) = source {
+                    out.push((path(), entry.oid.to_owned()));
+                }
+            }
+        }
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(out)
+    }
+
+    /// Add the `ids` of blobs with attribute patterns which aren't tied to a directory to `id_mappings`, as obtained by
+    /// [`id_mappings_from_index()`](Self::id_mappings_from_index()), to have them loaded as globals.
+    ///
+    /// This is useful if the file configured in `core.attributesFile` was resolved to a blob elsewhere, so that the index
+    /// can be the only source of attributes.
+    /// Their path is [`attributes::GLOBALS_ID_MAPPING_PATH`] so they can't be confused with files in the index.
+    ///
+    /// As globals, they have a lower precedence than `$GIT_DIR/info/attributes` and all `.gitattributes` files.
+    /// They are loaded after the global files an [`Attributes`] instance was created with, and later `ids` take precedence
+    /// over earlier ones, so the system-wide attributes should come first.
+    #[cfg(feature = "attributes")]
+    pub fn add_global_attributes_to_id_mappings(
+        id_mappings: &mut Vec<PathIdMapping>,
+        ids: impl IntoIterator<Item = gix_hash::ObjectId>,
+    ) {
+        id_mappings.extend(
+            ids.into_iter()
+                .map(|id| (attributes::GLOBALS_ID_MAPPING_PATH.into(), id)),
+        );
+        // Lookups rely on these being sorted, and the stable sort keeps globals in order.
+        id_mappings.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    /// Return the pattern that decides whether the repository-`relative_path` is excluded, or `None` if no pattern matches
+    /// or if no exclude information is configured.
+    ///
+    /// Just like `git check-ignore`, this works purely on `relative_path` without accessing the filesystem, so the path
+    /// doesn't have to exist. `is_dir` should be `Some(true)` if `relative_path` is meant to be a directory, and `case`
+    /// controls case-sensitivity of the match.
+    /// Like with [`Stack::at_entry()`](crate::Stack::at_entry()), a `relative_path` ending with `/` is assumed to be a
+    /// directory unless `is_dir` says otherwise, so `dir/` is matched as directory `dir`.
+    ///
+    /// Note that the returned pattern might be negated, which means `relative_path` is included.
+    /// Also note that per-directory ignore files are only considered as far as they are currently loaded, i.e. after
+    /// navigating a [`Stack`](crate::Stack) to the path's directory.
+    pub fn would_ignore(
+        &self,
+        relative_path: &BStr,
+        is_dir: Option<bool>,
+        case: Case,
+    ) -> Option<gix_ignore::search::Match<'_>> {
+        let (relative_path, is_dir) = match relative_path.strip_suffix(b"/") {
+            Some(dir) => (dir.as_bstr(), is_dir.or(Some(true))),
+            None => (relative_path, is_dir),
+        };
+        self.ignore()?
+            .matching_exclude_pattern_by_path(relative_path, is_dir, case)
+    }
+
+    pub(crate) fn ignore_or_panic(&self) -> &Ignore {
+        self.ignore()
+            .expect("BUG: must not try to check excludes without it being setup")
+    }
+
     #[cfg(feature = "attributes")]
     pub(crate) fn attributes_or_panic(&self) -> &Attributes {
+        self.attributes()
+            .expect("BUG: must not try to check attributes without it being setup")
+    }
+}
+
+/// Access
+impl State {
+    /// Return the exclude information, or `None` if this state wasn't configured to provide it.
+    pub fn ignore(&self) -> Option<&Ignore> {
+        match self {
+            State::IgnoreStack(ignore) => Some(ignore),
+            #[cfg(feature = "attributes")]
+            State::AttributesAndIgnoreStack { ignore, .. } => Some(ignore),
+            #[cfg(feature = "attributes")]
+            State::AttributesStack(_) | State::CreateDirectoryAndAttributesStack { .. } => None,
+        }
+    }
+
+    /// Return the attribute information, or `None` if this state wasn't configured to provide it, like
+    /// a state created with [`for_status()`](Self::for_status()).
+    #[cfg(feature = "attributes")]
+    pub fn attributes(&self) -> Option<&Attributes> {
         match self {
             State::AttributesStack(attributes)
             | State::AttributesAndIgnoreStack { attributes, .. }
-            | State::CreateDirectoryAndAttributesStack { attributes, .. } => attributes,
-            State::IgnoreStack(_) => {
-                unreachable!("BUG: must not try to check excludes without it being setup")
-            }
+            | State::CreateDirectoryAndAttributesStack { attributes, .. } => Some(attributes),
+            State::IgnoreStack(_) => None,
         }
     }
 }
@@ -0,0 +1,34 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+mkdir -p build a/b one/two
+cat <<EOF >.gitignore
+build/
+!build/keep
+a/
+/one/*
+!/one/two
+EOF
+
+# These are never read by git as their directory is excluded, hence they can't re-include anything.
+echo '!b/' >a/.gitignore
+echo '!c' >a/b/.gitignore
+
+cat <<EOF >one/two/.gitignore
+*.o
+!keep.o
+EOF
+
+git check-ignore -vn --stdin 2>&1 <<EOF >git-check-ignore.baseline || :
+build
+build/keep
+a
+a/b
+a/b/c
+one/other
+one/two
+one/two/x.o
+one/two/keep.o
+EOF
@@ -2,7 +2,7 @@ use bstr::ByteSlice;
 use gix_attributes::search::Outcome;
 use gix_worktree::stack::state;
 
-use crate::worktree::stack::probe_case;
+use crate::worktree::stack::{id_mappings, index_with_blobs, probe_case};
 
 #[test]
 fn baseline() -> crate::Result {
@@ -68,6 +68,733 @@ fn baseline() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn batched_matches_are_the_same_as_matching_one_path_at_a_time() -> crate::Result {
+    let case = probe_case()?;
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_attributes_baseline.sh")?;
+    let base = dir.join("basics");
+    let new_cache = || -> crate::Result<gix_worktree::Stack> {
+        let mut buf = Vec::new();
+        let mut collection = gix_attributes::search::MetadataCollection::default();
+        let state = gix_worktree::stack::State::for_checkout(
+            false,
+            state::Attributes::new(
+                gix_attributes::Search::new_globals([base.join("user.attributes")], &mut buf, &mut collection)?,
+                Some(base.join(".git").join("info").join("attributes")),
+                gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+                collection,
+            ),
+        );
+        Ok(gix_worktree::Stack::new(&base, state, case, buf, vec![]))
+    };
+
+    let input = std::fs::read(base.join("baseline"))?;
+    // Reverse the paths to have them visited out of order, one at a time.
+    let mut paths: Vec<_> = (baseline::Expectations { lines: input.lines() })
+        .map(|(rela_path, _)| rela_path)
+        .collect();
+    paths.reverse();
+    let assignments = |out: &Outcome| -> Vec<_> {
+        out.iter()
+            .filter(|m| !m.assignment.state.is_unspecified())
+            .map(|m| m.assignment.to_owned())
+            .collect()
+    };
+
+    let mut one_by_one = new_cache()?;
+    let mut out = one_by_one.attribute_matches();
+    let mut expected = Vec::new();
+    for rela_path in &paths {
+        one_by_one
+            .at_entry(*rela_path, None, &gix_object::find::Never)?
+            .matching_attributes(&mut out);
+        expected.push(assignments(&out));
+    }
+
+    let mut batched = new_cache()?;
+    let template = batched.attribute_matches();
+    let actual: Vec<_> = batched
+        .matching_attributes_for_entries(&paths, &template, &gix_object::find::Never)?
+        .iter()
+        .map(assignments)
+        .collect();
+    assert_eq!(actual, expected, "the outcomes are the same, in input order");
+    assert!(
+        batched.statistics().delegate.push_directory < one_by_one.statistics().delegate.push_directory,
+        "sorting the paths avoids re-entering directories"
+    );
+    Ok(())
+}
+
+#[test]
+fn global_files_have_lowest_precedence() -> crate::Result {
+    let case = probe_case()?;
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_attributes_baseline.sh")?;
+    let base = dir.join("basics");
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let overridden_global = tmp.path().join("attributes");
+    std::fs::write(&overridden_global, "f test=global\n")?;
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+    let attributes = state::Attributes::new(
+        globals,
+        None,
+        gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+        collection,
+    )
+    .with_global_file(tmp.path().join("does-not-exist"))?
+    .with_global_file(overridden_global)?
+    .with_global_file(base.join("user.attributes"))?;
+    let mut cache = gix_worktree::Stack::new(
+        &base,
+        gix_worktree::stack::State::for_checkout(false, attributes),
+        case,
+        buf,
+        vec![],
+    );
+
+    let mut actual = cache.selected_attribute_matches(["test", "global-no-wildcard-case-test"]);
+    for (rela_path, expected) in [
+        ("z/x/a", vec!["global-no-wildcard-case-test"]),
+        ("global", vec!["test=global"]),
+        ("f", vec!["test=f"]),
+    ] {
+        let entry = cache.at_entry(rela_path, None, &gix_object::find::Never)?;
+        assert!(entry.matching_attributes(&mut actual));
+        let actual: Vec<_> = actual
+            .iter_selected()
+            .filter(|m| !m.assignment.state.is_unspecified())
+            .map(|m| m.assignment.to_string())
+            .collect();
+        assert_eq!(actual, expected, "{rela_path}: `.gitattributes` files override globals");
+    }
+    Ok(())
+}
+
+#[test]
+fn trailing_slashes_mark_directories() -> crate::Result {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let global = tmp.path().join("attributes");
+    std::fs::write(&global, "dir test=any\ndir/ test=dir\n")?;
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+    let attributes = state::Attributes::new(globals, None, state::attributes::Source::IdMapping, collection)
+        .with_global_file(global)?;
+    let mut cache = gix_worktree::Stack::new(
+        tmp.path(),
+        gix_worktree::stack::State::AttributesStack(attributes),
+        gix_glob::pattern::Case::Fold,
+        buf,
+        vec![],
+    );
+
+    let mut actual = cache.selected_attribute_matches(["test"]);
+    for (rela_path, is_dir, expected) in [
+        ("dir/", None, "test=dir"),
+        ("DIR/", None, "test=dir"),
+        ("dir", None, "test=any"),
+        ("dir", Some(true), "test=dir"),
+        ("dir/", Some(false), "test=any"),
+    ] {
+        actual.reset();
+        let entry = cache.at_entry(rela_path, is_dir, &gix_object::find::Never)?;
+        assert!(entry.matching_attributes(&mut actual));
+        assert_eq!(
+            actual
+                .iter_selected()
+                .next()
+                .map(|m| m.assignment.to_string())
+                .as_deref(),
+            Some(expected),
+            "{rela_path} ({is_dir:?}): directory patterns only match directories, and a trailing slash marks one"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn multiple_info_attributes_files_are_loaded_once_in_order() -> crate::Result {
+    let case = probe_case()?;
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_attributes_baseline.sh")?;
+    let base = dir.join("basics");
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let main_info = tmp.path().join("main-attributes");
+    let worktree_info = tmp.path().join("worktree-attributes");
+    std::fs::write(&main_info, "info-only test=main\nmain-only test=main\n")?;
+    std::fs::write(&worktree_info, "info-only test=worktree\n")?;
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+    let attributes = state::Attributes::new(
+        globals,
+        [main_info.clone(), worktree_info, main_info],
+        gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+        collection,
+    );
+    let mut cache = gix_worktree::Stack::new(
+        &base,
+        gix_worktree::stack::State::for_checkout(false, attributes),
+        case,
+        buf,
+        vec![],
+    );
+
+    let mut actual = cache.selected_attribute_matches(["test"]);
+    for (rela_path, expected) in [("info-only", "test=worktree"), ("main-only", "test=main")] {
+        let entry = cache.at_entry(rela_path, None, &gix_object::find::Never)?;
+        assert!(entry.matching_attributes(&mut actual));
+        let actual: Vec<_> = actual.iter_selected().map(|m| m.assignment.to_string()).collect();
+        assert_eq!(
+            actual,
+            [expected],
+            "{rela_path}: later files win, and duplicates aren't loaded again"
+        );
+    }
+    assert_eq!(
+        cache.statistics().attributes.tried_pattern_files,
+        2 + 1,
+        "both info files, along with the root `.gitattributes`"
+    );
+    Ok(())
+}
+
+#[test]
+fn worktree_and_id_mapping_sources_fall_back_per_file() -> crate::Result {
+    use gix_worktree::stack::state::attributes::Source;
+
+    let case = probe_case()?;
+    let (tmp, odb, index) = index_with_blobs(&[
+        ("a/.gitattributes", b"f from-index-a\n"),
+        ("b/.gitattributes", b"f from-index-b\n"),
+    ])?;
+    let id_mappings = id_mappings(&index);
+
+    let worktree = tmp.path().join("worktree");
+    std::fs::create_dir_all(worktree.join("a"))?;
+    std::fs::create_dir_all(worktree.join("b"))?;
+    std::fs::write(worktree.join("a").join(".gitattributes"), "f from-worktree-a\n")?;
+
+    for (source, expected_a) in [
+        (Source::WorktreeThenIdMapping, "from-worktree-a"),
+        (Source::IdMappingThenWorktree, "from-index-a"),
+    ] {
+        let mut buf = Vec::new();
+        let mut collection = gix_attributes::search::MetadataCollection::default();
+        let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+        let mut cache = gix_worktree::Stack::new(
+            &worktree,
+            gix_worktree::stack::State::AttributesStack(state::Attributes::new(globals, None, source, collection)),
+            case,
+            buf,
+            id_mappings.clone(),
+        );
+
+        let mut actual = cache.attribute_matches();
+        for (rela_path, expected) in [("a/f", expected_a), ("b/f", "from-index-b")] {
+            let entry = cache.at_entry(rela_path, None, &odb)?;
+            assert!(entry.matching_attributes(&mut actual));
+            let actual: Vec<_> = actual
+                .iter()
+                .filter(|m| !m.assignment.state.is_unspecified())
+                .map(|m| m.assignment.to_string())
+                .collect();
+            assert_eq!(
+                actual,
+                [expected],
+                "{source:?}: {rela_path} uses the preferred source if it has the file, and the other one otherwise"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn byte_order_marks_and_crlf_line_endings_are_tolerated() -> crate::Result {
+    let content = "\u{feff}f first\r\ng second\r\n";
+    let (tmp, odb, index) = index_with_blobs(&[("b/.gitattributes", content.as_bytes())])?;
+    let id_mappings = id_mappings(&index);
+
+    let worktree = tmp.path().join("worktree");
+    std::fs::create_dir_all(worktree.join("a"))?;
+    std::fs::write(worktree.join("a").join(".gitattributes"), content)?;
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+    let mut cache = gix_worktree::Stack::new(
+        &worktree,
+        gix_worktree::stack::State::AttributesStack(state::Attributes::new(
+            globals,
+            None,
+            state::attributes::Source::WorktreeThenIdMapping,
+            collection,
+        )),
+        gix_glob::pattern::Case::Sensitive,
+        buf,
+        id_mappings,
+    );
+
+    let mut actual = cache.attribute_matches();
+    for (rela_path, expected) in [("a/f", "first"), ("a/g", "second"), ("b/f", "first"), ("b/g", "second")] {
+        let entry = cache.at_entry(rela_path, None, &odb)?;
+        assert!(entry.matching_attributes(&mut actual));
+        let actual: Vec<_> = actual
+            .iter()
+            .filter(|m| !m.assignment.state.is_unspecified())
+            .map(|m| m.assignment.to_string())
+            .collect();
+        assert_eq!(
+            actual,
+            [expected],
+            "{rela_path}: the BOM isn't part of the first pattern, and CR isn't part of any attribute, no matter the source"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn collection_snapshots_round_trip_and_yield_identical_matches() -> crate::Result {
+    use gix_odb::Write;
+
+    let (tmp, odb, index) = index_with_blobs(&[
+        (
+            ".gitattributes",
+            b"[attr]binary -diff -merge -text\n*.bin binary\n*.txt text eol=lf\n",
+        ),
+        ("sub/.gitattributes", b"*.txt -text custom=value\n"),
+    ])?;
+    let mut id_mappings = id_mappings(&index);
+
+    let new_cache = |collection| -> crate::Result<_> {
+        let mut buf = Vec::new();
+        let mut globals_collection = gix_attributes::search::MetadataCollection::default();
+        let globals =
+            gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut globals_collection)?;
+        Ok(gix_worktree::Stack::new(
+            tmp.path(),
+            gix_worktree::stack::State::AttributesStack(state::Attributes::new(
+                globals,
+                None,
+                state::attributes::Source::IdMapping,
+                collection,
+            )),
+            gix_glob::pattern::Case::Sensitive,
+            buf,
+            id_mappings.clone(),
+        ))
+    };
+    let matches = |cache: &mut gix_worktree::Stack| -> crate::Result<Vec<Vec<String>>> {
+        let mut out = Vec::new();
+        for rela_path in ["a.bin", "a.txt", "sub/a.txt", "sub/a.bin"] {
+            let mut actual = cache.attribute_matches();
+            cache.at_entry(rela_path, None, &odb)?.matching_attributes(&mut actual);
+            out.push(actual.iter().map(|m| m.assignment.to_string()).collect());
+        }
+        Ok(out)
+    };
+
+    let mut cache = new_cache(Default::default())?;
+    let expected = matches(&mut cache)?;
+    let snapshot = cache
+        .state()
+        .attributes()
+        .expect("configured")
+        .collection_snapshot(&id_mappings);
+    let restored: state::attributes::CollectionSnapshot = serde_json::from_str(&serde_json::to_string(&snapshot)?)?;
+    assert!(restored.is_valid_for(&id_mappings));
+
+    let mut cache = new_cache(restored.collection.clone())?;
+    assert_eq!(
+        matches(&mut cache)?,
+        expected,
+        "the restored collection yields the same matches"
+    );
+
+    id_mappings.push((
+        "sub/.gitignore".into(),
+        odb.write_buf(gix_object::Kind::Blob, b"*.o\n")?,
+    ));
+    id_mappings.sort_by(|a, b| a.0.cmp(&b.0));
+    assert!(
+        restored.is_valid_for(&id_mappings),
+        "mappings of files other than attribute files don't matter"
+    );
+    id_mappings[1].1 = odb.write_buf(gix_object::Kind::Blob, b"*.txt -text\n")?;
+    assert_eq!(id_mappings[1].0, "sub/.gitattributes");
+    assert!(
+        !restored.is_valid_for(&id_mappings),
+        "a changed attribute file invalidates the snapshot"
+    );
+    Ok(())
+}
+
+#[test]
+fn id_mappings_from_tree_find_nested_files_at_any_depth() -> crate::Result {
+    use gix_object::tree::{Entry, EntryKind};
+    use gix_odb::Write;
+
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let odb = gix_odb::at(tmp.path())?;
+    let blob = |content: &str| odb.write_buf(gix_object::Kind::Blob, content.as_bytes());
+    let entry = |mode: EntryKind, filename: &str, oid| Entry {
+        mode: mode.into(),
+        filename: filename.into(),
+        oid,
+    };
+    let tree = |entries| odb.write(&gix_object::Tree { entries });
+
+    let b = tree(vec![entry(EntryKind::Blob, ".gitattributes", blob("f in-b\n")?)])?;
+    let a = tree(vec![
+        entry(EntryKind::Blob, ".gitattributes", blob("f in-a\n")?),
+        entry(EntryKind::Tree, "b", b),
+    ])?;
+    let z = tree(vec![
+        entry(EntryKind::BlobExecutable, ".gitattributes", blob("f in-z\n")?),
+        entry(
+            EntryKind::Tree,
+            "y",
+            tree(vec![entry(EntryKind::Blob, ".gitattributes", blob("f in-y\n")?)])?,
+        ),
+    ])?;
+    let root = tree(vec![
+        entry(EntryKind::Blob, ".gitattributes", blob("* in-root\n")?),
+        entry(EntryKind::Tree, "a", a),
+        entry(
+            EntryKind::Commit,
+            "sub",
+            gix_hash::ObjectId::from_hex(b"1111111111111111111111111111111111111111")?,
+        ),
+        entry(EntryKind::Tree, "z", z),
+    ])?;
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+    let state = gix_worktree::stack::State::AttributesStack(state::Attributes::new(
+        globals,
+        None,
+        state::attributes::Source::IdMapping,
+        collection,
+    ));
+    let case = gix_glob::pattern::Case::Sensitive;
+    let id_mappings = state.id_mappings_from_tree(&root, &odb, case)?;
+    assert_eq!(
+        id_mappings.iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>(),
+        [
+            ".gitattributes",
+            "a/.gitattributes",
+            "a/b/.gitattributes",
+            "z/y/.gitattributes"
+        ],
+        "like with the index, only non-executable files are used, the submodule isn't traversed, and paths are sorted"
+    );
+
+    let mut cache = gix_worktree::Stack::new(tmp.path().join("no-worktree"), state, case, buf, id_mappings);
+    let mut actual = cache.attribute_matches();
+    let entry = cache.at_entry("a/b/f", None, &odb)?;
+    assert!(entry.matching_attributes(&mut actual));
+    let mut actual: Vec<_> = actual.iter().map(|m| m.assignment.to_string()).collect();
+    actual.sort();
+    assert_eq!(
+        actual,
+        ["in-a", "in-b", "in-root"],
+        "files at all levels are used, without a worktree"
+    );
+    Ok(())
+}
+
+#[test]
+fn attribute_sources_explain_matches_of_nested_files() -> crate::Result {
+    use gix_worktree::stack::state::attributes::Source;
+
+    let case = probe_case()?;
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let worktree = tmp.path();
+    std::fs::create_dir_all(worktree.join("dir/sub"))?;
+    std::fs::create_dir_all(worktree.join("other"))?;
+    std::fs::write(worktree.join(".gitattributes"), "* text\n")?;
+    std::fs::write(worktree.join("dir/.gitattributes"), "# comment\n*.rs diff=rust\n")?;
+    std::fs::write(worktree.join("dir/sub/.gitattributes"), "")?;
+    std::fs::write(worktree.join("other/.gitattributes"), "*.rs -diff\n")?;
+    std::fs::write(worktree.join("user.attributes"), "*.rs merge=union\n")?;
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals([worktree.join("user.attributes")], &mut buf, &mut collection)?;
+    let mut cache = gix_worktree::Stack::new(
+        worktree,
+        gix_worktree::stack::State::AttributesStack(state::Attributes::new(
+            globals,
+            None,
+            Source::WorktreeThenIdMapping,
+            collection,
+        )),
+        case,
+        buf,
+        Vec::new(),
+    );
+
+    let mut actual = cache.attribute_matches();
+    actual.set_trace(true);
+    let entry = cache.at_entry("dir/sub/file.rs", Some(false), &gix_object::find::Never)?;
+    assert!(entry.matching_attributes(&mut actual));
+    assert_eq!(
+        entry.attribute_sources()?,
+        [
+            "[builtin]".into(),
+            worktree.join("user.attributes"),
+            worktree.join(".gitattributes"),
+            worktree.join("dir/.gitattributes")
+        ],
+        "built-in macros come first, and files without patterns or of unrelated directories aren't listed"
+    );
+
+    let diff = actual
+        .iter()
+        .find(|m| m.assignment.name.as_str() == "diff")
+        .expect("diff is set");
+    assert_eq!(diff.assignment.to_string(), "diff=rust");
+    assert_eq!(
+        diff.location.source,
+        Some(worktree.join("dir/.gitattributes").as_path()),
+        "the nested file decided the value"
+    );
+    assert_eq!(diff.location.sequence_number, 2, "lines are 1-based");
+
+    let trace: Vec<_> = actual
+        .trace()
+        .map(|e| (e.location.source, e.location.sequence_number, e.pattern.to_string()))
+        .collect();
+    assert_eq!(
+        trace,
+        [
+            (Some(worktree.join("dir/.gitattributes").as_path()), 2, "*.rs".into()),
+            (Some(worktree.join(".gitattributes").as_path()), 1, "*".into()),
+            (Some(worktree.join("user.attributes").as_path()), 1, "*.rs".into()),
+        ],
+        "the trace lists each pattern that applied, from the nested file to the global one"
+    );
+    Ok(())
+}
+
+#[test]
+fn global_attributes_can_be_provided_by_id_mappings() -> crate::Result {
+    use gix_odb::Write;
+    use gix_worktree::stack::state::attributes::{Source, GLOBALS_ID_MAPPING_PATH};
+
+    let case = probe_case()?;
+    let (tmp, odb, index) = index_with_blobs(&[("dir/.gitattributes", b"f in-tree c=in-tree\n")])?;
+    let global_file = tmp.path().join("system-attributes");
+    std::fs::write(&global_file, "* system a=system b=system c=system\n")?;
+
+    let mut id_mappings = id_mappings(&index);
+    gix_worktree::stack::State::add_global_attributes_to_id_mappings(
+        &mut id_mappings,
+        [
+            odb.write_buf(gix_object::Kind::Blob, b"* first a=first b=first c=first\n")?,
+            odb.write_buf(gix_object::Kind::Blob, b"* second b=second c=second\n")?,
+        ],
+    );
+    assert_eq!(
+        id_mappings.iter().map(|t| t.0.to_str_lossy()).collect::<Vec<_>>(),
+        [GLOBALS_ID_MAPPING_PATH, GLOBALS_ID_MAPPING_PATH, "dir/.gitattributes"],
+        "mappings remain sorted for lookup, with globals in order"
+    );
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+    let attributes =
+        state::Attributes::new(globals, None, Source::IdMapping, collection).with_global_file(global_file)?;
+    let mut cache = gix_worktree::Stack::new(
+        tmp.path().join("worktree"),
+        gix_worktree::stack::State::AttributesStack(attributes),
+        case,
+        buf,
+        id_mappings,
+    );
+
+    let mut actual = cache.attribute_matches();
+    for _round in 0..2 {
+        let entry = cache.at_entry("dir/f", None, &odb)?;
+        assert!(entry.matching_attributes(&mut actual));
+        let mut assignments: Vec<_> = actual
+            .iter()
+            .filter(|m| !m.assignment.state.is_unspecified())
+            .map(|m| m.assignment.to_string())
+            .collect();
+        assignments.sort();
+        assert_eq!(
+            assignments,
+            [
+                "a=first",
+                "b=second",
+                "c=in-tree",
+                "first",
+                "in-tree",
+                "second",
+                "system"
+            ],
+            "later globals override earlier ones, and in-tree files override all of them"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn clear_stack_keeps_globals_but_forgets_patterns_of_directories() -> crate::Result {
+    let case = probe_case()?;
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let global = tmp.path().join("global-attributes");
+    let info = tmp.path().join("info-attributes");
+    std::fs::write(&global, "f global\n")?;
+    std::fs::write(&info, "f info\n")?;
+    let first = tmp.path().join("first");
+    let second = tmp.path().join("second");
+    std::fs::create_dir_all(first.join("dir"))?;
+    std::fs::create_dir(&second)?;
+    std::fs::write(first.join(".gitattributes"), "f first-root\n")?;
+    std::fs::write(first.join("dir").join(".gitattributes"), "f first-dir\n")?;
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+    let attributes = state::Attributes::new(
+        globals,
+        Some(info),
+        gix_worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+        collection,
+    )
+    .with_global_file(global)?;
+
+    fn matches(cache: &mut gix_worktree::Stack, rela_path: &str) -> crate::Result<Vec<String>> {
+        let mut out = cache.attribute_matches();
+        cache
+            .at_entry(rela_path, Some(false), &gix_object::find::Never)?
+            .matching_attributes(&mut out);
+        let mut names: Vec<_> = out
+            .iter()
+            .filter(|m| !m.assignment.state.is_unspecified())
+            .map(|m| m.assignment.name.as_str().to_owned())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    let mut cache = gix_worktree::Stack::new(
+        &first,
+        gix_worktree::stack::State::AttributesStack(attributes),
+        case,
+        buf.clone(),
+        vec![],
+    );
+    assert_eq!(
+        matches(&mut cache, "dir/f")?,
+        ["first-dir", "first-root", "global", "info"]
+    );
+
+    let mut attributes = match cache.state() {
+        gix_worktree::stack::State::AttributesStack(attributes) => attributes.clone(),
+        _ => unreachable!("we configured attributes only"),
+    };
+    attributes.clear_stack();
+    let mut cache = gix_worktree::Stack::new(
+        &second,
+        gix_worktree::stack::State::AttributesStack(attributes),
+        case,
+        buf,
+        vec![],
+    );
+    assert_eq!(
+        matches(&mut cache, "dir/f")?,
+        ["global", "info"],
+        "globals and info attributes apply to the new walk, but nothing of the previous directories"
+    );
+    assert_eq!(
+        cache.statistics().attributes.pattern_files,
+        1,
+        "only the info attributes file is read again, globals are kept"
+    );
+    Ok(())
+}
+
+#[test]
+fn changing_the_source_re_reads_attribute_files_of_directories() -> crate::Result {
+    use gix_worktree::stack::state::attributes::Source;
+
+    let case = probe_case()?;
+    let (tmp, odb, index) = index_with_blobs(&[("dir/.gitattributes", b"f index\n")])?;
+    let id_mappings = id_mappings(&index);
+    let info = tmp.path().join("info-attributes");
+    std::fs::write(&info, "f info\n")?;
+    let worktree = tmp.path().join("worktree");
+    std::fs::create_dir_all(worktree.join("dir"))?;
+    std::fs::write(worktree.join("dir").join(".gitattributes"), "f worktree\n")?;
+
+    let mut buf = Vec::new();
+    let mut collection = gix_attributes::search::MetadataCollection::default();
+    let globals = gix_attributes::Search::new_globals(None::<std::path::PathBuf>, &mut buf, &mut collection)?;
+    let attributes = state::Attributes::new(globals, Some(info), Source::IdMapping, collection);
+    let mut cache = gix_worktree::Stack::new(
+        &worktree,
+        gix_worktree::stack::State::AttributesStack(attributes),
+        case,
+        buf,
+        id_mappings,
+    );
+
+    let matches = |cache: &mut gix_worktree::Stack| -> crate::Result<Vec<String>> {
+        let mut out = cache.attribute_matches();
+        cache
+            .at_entry("dir/f", Some(false), &odb)?
+            .matching_attributes(&mut out);
+        let mut names: Vec<_> = out
+            .iter()
+            .filter(|m| !m.assignment.state.is_unspecified())
+            .map(|m| m.assignment.name.as_str().to_owned())
+            .collect();
+        names.sort();
+        Ok(names)
+    };
+    assert_eq!(matches(&mut cache)?, ["index", "info"]);
+
+    cache.set_attributes_source(Source::WorktreeThenIdMapping)?;
+    assert!(matches!(
+        cache.state().attributes().expect("configured").source(),
+        Source::WorktreeThenIdMapping
+    ));
+    assert_eq!(
+        matches(&mut cache)?,
+        ["info", "worktree"],
+        "the attribute file of the directory is read again from the new source, and info attributes are kept"
+    );
+
+    cache.set_attributes_source(Source::IdMapping)?;
+    assert_eq!(matches(&mut cache)?, ["index", "info"], "it's possible to switch back");
+
+    let mut cache = gix_worktree::Stack::new(
+        &worktree,
+        gix_worktree::stack::State::for_status(Default::default(), None),
+        case,
+        Vec::new(),
+        Vec::new(),
+    );
+    assert_eq!(
+        cache
+            .set_attributes_source(Source::IdMapping)
+            .map(|_| ())
+            .unwrap_err()
+            .needed,
+        "attribute"
+    );
+    Ok(())
+}
+
 fn assert_references(out: &Outcome) {
     for m in out.iter() {
         if let Some(source) = m.kind.source_id() {
@@ -132,3 +859,40 @@ mod baseline {
         }
     }
 }
+
+#[test]
+fn missing_state_components_are_reported_as_errors() -> crate::Result {
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let state = gix_worktree::stack::State::for_status(Default::default(), None);
+    assert!(state.attributes().is_none());
+    assert!(state.ignore().is_some());
+    let mut cache = gix_worktree::Stack::new(tmp.path(), state, Default::default(), Vec::new(), Vec::new());
+
+    let platform = cache.at_entry("file", Some(false), &gix_object::find::Never)?;
+    let err = platform
+        .try_matching_attributes(&mut Default::default())
+        .expect_err("status stacks have no attributes");
+    assert_eq!(err.needed, "attribute");
+    assert_eq!(
+        err.to_string(),
+        "Cannot provide attribute information as the stack wasn't configured for it"
+    );
+    assert_eq!(platform.try_matching_exclude_pattern()?, None, "excludes are available");
+
+    let mut cache = gix_worktree::Stack::new(
+        tmp.path(),
+        gix_worktree::stack::State::AttributesStack(Default::default()),
+        Default::default(),
+        Vec::new(),
+        Vec::new(),
+    );
+    let err = cache
+        .matching_exclude_pattern("file", Some(false), &gix_object::find::Never)
+        .expect_err("no exclude information");
+    assert!(
+        err.get_ref()
+            .is_some_and(<dyn std::error::Error + Send + Sync>::is::<gix_worktree::stack::StateMismatch>),
+        "the error is passed as IO error"
+    );
+    Ok(())
+}
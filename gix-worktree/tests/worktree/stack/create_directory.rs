@@ -106,11 +106,131 @@ fn symlinks_or_files_in_path_are_forbidden_or_unlinked_when_forced() -> crate::R
     Ok(())
 }
 
+#[test]
+fn case_colliding_files_in_path_are_unlinked_when_forced() -> crate::Result {
+    let (mut cache, tmp) = new_cache_with(true);
+    std::fs::write(tmp.path().join("File.txt"), [])?;
+    let ignore_case = tmp.path().join("FILE.TXT").exists();
+
+    let path = cache
+        .at_path("file.txt/x", Some(false), &gix_object::find::Never)?
+        .path();
+    assert!(
+        path.parent().unwrap().is_dir(),
+        "directory was created in place of the file"
+    );
+    if ignore_case {
+        assert!(!tmp.path().join("File.txt").is_file(), "the colliding file was removed");
+        assert_eq!(
+            cache.statistics().delegate.num_mkdir_calls,
+            2,
+            "the first attempt collided with the file"
+        );
+    } else {
+        assert!(tmp.path().join("File.txt").is_file(), "nothing collided");
+        assert_eq!(cache.statistics().delegate.num_mkdir_calls, 1);
+    }
+    Ok(())
+}
+
+#[test]
+fn symlinks_and_empty_directories_in_place_of_files_are_unlinked_when_forced() -> crate::Result {
+    for unlink_on_collision in [false, true] {
+        let (mut cache, tmp) = new_cache_with(unlink_on_collision);
+        let target = tmp.path().join("target");
+        std::fs::create_dir(&target)?;
+        symlink::symlink_dir(&target, tmp.path().join("link"))?;
+        std::fs::create_dir(tmp.path().join("empty"))?;
+        std::fs::create_dir(tmp.path().join("full"))?;
+        std::fs::write(tmp.path().join("full").join("file"), [])?;
+
+        for name in ["link", "empty"] {
+            let path = cache.at_path(name, Some(false), &gix_object::find::Never)?.path();
+            assert_eq!(
+                path.symlink_metadata().is_ok(),
+                !unlink_on_collision,
+                "{name}: only removed when forced"
+            );
+        }
+        assert!(target.is_dir(), "the symlink target is never touched");
+
+        let res = cache.at_path("full", Some(false), &gix_object::find::Never);
+        if unlink_on_collision {
+            let err = res.map(|_| ()).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+            assert!(
+                err.to_string().starts_with("Refusing to replace non-empty directory"),
+                "{err}"
+            );
+        } else {
+            res?.path();
+        }
+        assert!(
+            tmp.path().join("full").join("file").is_file(),
+            "non-empty directories are never removed"
+        );
+        assert_eq!(
+            cache.statistics().delegate.num_mkdir_calls,
+            0,
+            "files don't need directories to be created"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn created_directories_can_be_tracked() -> crate::Result {
+    let (mut cache, tmp) = new_cache();
+    std::fs::create_dir(tmp.path().join("a"))?;
+    let _ = cache.at_path("untracked/file", Some(false), &gix_object::find::Never)?;
+    assert!(cache.created_directories().is_empty(), "tracking is off by default");
+
+    cache.set_track_created_directories(true);
+    let _ = cache.at_path("a/b/c/file", Some(false), &gix_object::find::Never)?;
+    assert_eq!(
+        cache.created_directories(),
+        [tmp.path().join("a/b"), tmp.path().join("a/b/c")],
+        "directories that existed already aren't listed"
+    );
+
+    let _ = cache.at_path("a/b/d/e/file", Some(false), &gix_object::find::Never)?;
+    assert_eq!(
+        cache.take_created_directories(),
+        [
+            tmp.path().join("a/b"),
+            tmp.path().join("a/b/c"),
+            tmp.path().join("a/b/d"),
+            tmp.path().join("a/b/d/e")
+        ],
+        "all created directories are listed in order of creation"
+    );
+    assert_eq!(
+        cache.statistics().delegate.num_mkdir_calls,
+        6,
+        "attempts to create existing directories are counted, but not recorded"
+    );
+
+    let _ = cache.at_path("a/f/file", Some(false), &gix_object::find::Never)?;
+    assert_eq!(
+        cache.created_directories(),
+        [tmp.path().join("a/f")],
+        "taking them keeps tracking enabled"
+    );
+
+    cache.set_track_created_directories(false);
+    assert!(cache.created_directories().is_empty(), "disabling discards the list");
+    Ok(())
+}
+
 fn new_cache() -> (Stack, TempDir) {
+    new_cache_with(false)
+}
+
+fn new_cache_with(unlink_on_collision: bool) -> (Stack, TempDir) {
     let dir = tempdir().unwrap();
     let cache = Stack::new(
         dir.path(),
-        stack::State::for_checkout(false, Default::default()),
+        stack::State::for_checkout(unlink_on_collision, Default::default()),
         Default::default(),
         Vec::new(),
         Default::default(),
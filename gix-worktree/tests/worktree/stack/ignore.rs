@@ -1,7 +1,11 @@
 use bstr::{BStr, ByteSlice};
+use gix_glob::pattern::Case;
 use gix_worktree::{stack::state::ignore::Source, Stack};
 
-use crate::{hex_to_id, worktree::stack::probe_case};
+use crate::{
+    hex_to_id,
+    worktree::stack::{id_mappings, index_with_blobs, probe_case},
+};
 
 struct IgnoreExpectations<'a> {
     lines: bstr::Lines<'a>,
@@ -87,6 +91,87 @@ fn exclude_by_dir_is_handled_just_like_git() {
     }
 }
 
+#[test]
+fn paths_cannot_be_reincluded_if_a_parent_directory_is_excluded() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_ignore_reinclude_cases.sh")?;
+    let git_dir = dir.join(".git");
+
+    let mut buf = Vec::new();
+    let state = gix_worktree::stack::State::for_add(
+        Default::default(),
+        gix_worktree::stack::state::Ignore::new(
+            Default::default(),
+            gix_ignore::Search::from_git_dir(&git_dir, None, &mut buf)?,
+            None,
+            Source::WorktreeThenIdMappingIfNotSkipped,
+        ),
+    );
+    let mut cache = Stack::new(&dir, state, Case::Sensitive, buf, Default::default());
+    let baseline = std::fs::read(dir.join("git-check-ignore.baseline"))?;
+    for (relative_entry, source_and_line) in (IgnoreExpectations {
+        lines: baseline.lines(),
+    }) {
+        let (source, line, expected_pattern) = source_and_line.expect("every value is matched");
+        let is_dir = dir
+            .join(gix_path::from_bstr(relative_entry))
+            .metadata()
+            .ok()
+            .map(|m| m.is_dir());
+        let m = cache
+            .matching_exclude_pattern(relative_entry, is_dir, &gix_object::find::Never)?
+            .expect("every value is matched");
+        assert_eq!(
+            (m.pattern.to_string().as_str(), m.sequence_number),
+            (expected_pattern.to_str()?, line),
+            "{relative_entry}: we agree with git"
+        );
+        assert_eq!(
+            m.source.map(|p| p.strip_prefix(&dir).expect("in worktree").to_owned()),
+            Some(gix_path::from_bstr(source).into_owned())
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn matching_exclude_pattern_on_stack_short_circuits_on_excluded_parent_directory() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_special_exclude_case.sh")?;
+    let git_dir = dir.join(".git");
+
+    let mut buf = Vec::new();
+    let state = gix_worktree::stack::State::for_add(
+        Default::default(),
+        gix_worktree::stack::state::Ignore::new(
+            Default::default(),
+            gix_ignore::Search::from_git_dir(&git_dir, None, &mut buf)?,
+            None,
+            Source::WorktreeThenIdMappingIfNotSkipped,
+        ),
+    );
+    let mut cache = Stack::new(&dir, state, Case::Sensitive, buf, Default::default());
+
+    for (relative_path, is_dir) in [("tld", Some(true)), ("tld/file", Some(false)), ("tld/sd/", None)] {
+        let m = cache
+            .matching_exclude_pattern(relative_path, is_dir, &gix_object::find::Never)?
+            .expect("excluded");
+        assert_eq!(
+            m.pattern.to_string(),
+            "tld/",
+            "{relative_path}: negations inside of the excluded directory don't matter"
+        );
+        assert_eq!(
+            m.source.map(|p| p.file_name().expect("set")),
+            Some(".gitignore".as_ref())
+        );
+        assert_eq!(m.sequence_number, 2, "the line number of the pattern");
+    }
+    assert_eq!(
+        cache.matching_exclude_pattern(".gitignore", Some(false), &gix_object::find::Never)?,
+        None
+    );
+    Ok(())
+}
+
 #[test]
 fn check_against_baseline() -> crate::Result {
     let dir = gix_testtools::scripted_fixture_read_only_standalone("make_ignore_and_attributes_setup.sh")?;
@@ -154,6 +239,12 @@ fn check_against_baseline() -> crate::Result {
                         m.source.map(|p| p.canonicalize().unwrap()),
                         Some(worktree_dir.join(source_file.to_str_lossy().as_ref()).canonicalize()?)
                     );
+                } else {
+                    assert_eq!(
+                        m.source,
+                        Some(gix_path::from_bstr(source_file).as_ref()),
+                        "the repo-relative path is used for ignore files read from the index"
+                    );
                 }
             }
             (Some(actual), None) if actual.pattern.is_negative() => {
@@ -166,3 +257,585 @@ fn check_against_baseline() -> crate::Result {
     }
     Ok(())
 }
+
+#[test]
+fn matches_know_their_source_file_and_line() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_read_only_standalone("make_ignore_and_attributes_setup.sh")?;
+    let worktree_dir = dir.join("repo");
+    let git_dir = worktree_dir.join(".git");
+    let mut buf = Vec::new();
+    let state = gix_worktree::stack::State::for_add(
+        Default::default(),
+        gix_worktree::stack::state::Ignore::new(
+            Default::default(),
+            gix_ignore::Search::from_git_dir(&git_dir, Some(dir.join("user.exclude")), &mut buf)?,
+            None,
+            Source::WorktreeThenIdMappingIfNotSkipped,
+        ),
+    );
+    let mut cache = Stack::new(&worktree_dir, state, Case::Sensitive, buf, Default::default());
+
+    for (relative_path, expected_source, expected_line) in [
+        (
+            "dir-with-ignore/sub-dir/sub-level-local-file-anywhere",
+            worktree_dir.join("dir-with-ignore").join(".gitignore"),
+            2,
+        ),
+        ("sub/file-anywhere", git_dir.join("info").join("exclude"), 2),
+        ("sub/user-file-anywhere", dir.join("user.exclude"), 2),
+    ] {
+        let m = cache
+            .matching_exclude_pattern(relative_path, Some(false), &gix_object::find::Never)?
+            .expect("excluded");
+        assert_eq!(
+            m.source,
+            Some(expected_source.as_path()),
+            "{relative_path}: nested, global and `info/exclude` files are all reported"
+        );
+        assert_eq!(m.sequence_number, expected_line, "{relative_path}: lines are 1-based");
+    }
+    Ok(())
+}
+
+#[test]
+fn id_mappings_from_index_with_precompose_unicode() {
+    let precomposed = ".gitign\u{f3}re";
+    let decomposed = "dir/.gitigno\u{301}re";
+    let mut index = gix_index::State::new(gix_hash::Kind::Sha1);
+    let id = hex_to_id("5c7e0ed672d3d31d83a3df61f13cc8f7b22d5bfd");
+    index.dangerously_push_entry(
+        Default::default(),
+        id,
+        gix_index::entry::Flags::empty(),
+        gix_index::entry::Mode::FILE,
+        decomposed.into(),
+    );
+    let state = gix_worktree::stack::State::IgnoreStack(gix_worktree::stack::state::Ignore::new(
+        Default::default(),
+        Default::default(),
+        Some(precomposed.into()),
+        Source::IdMapping,
+    ));
+
+    assert_eq!(
+        state.id_mappings_from_index(&index, index.path_backing(), Case::Sensitive),
+        vec![],
+        "without precomposition, the decomposed name in the index doesn't match"
+    );
+    assert_eq!(
+        state.id_mappings_from_index_with_precompose_unicode(&index, index.path_backing(), Case::Sensitive, true),
+        vec![(format!("dir/{precomposed}").into(), id)],
+        "the path is precomposed for comparison and in the returned mapping"
+    );
+}
+
+#[test]
+fn id_mappings_from_index_have_one_entry_per_path_with_our_stage() {
+    let mut index = gix_index::State::new(gix_hash::Kind::Sha1);
+    let id_of_stage = |stage: u32| hex_to_id(&format!("{stage}{}", "5c7e0ed672d3d31d83a3df61f13cc8f7b22d5bf"));
+    for (path, stage) in [
+        (".gitattributes", 0),
+        (".gitattributes", 2),
+        ("conflict/.gitattributes", 1),
+        ("conflict/.gitattributes", 2),
+        ("conflict/.gitattributes", 3),
+    ] {
+        index.dangerously_push_entry(
+            Default::default(),
+            id_of_stage(stage),
+            gix_index::entry::Flags::from_bits_retain(stage << 12),
+            gix_index::entry::Mode::FILE,
+            path.into(),
+        );
+    }
+    let state = gix_worktree::stack::State::AttributesStack(Default::default());
+    assert_eq!(
+        state.id_mappings_from_index(&index, index.path_backing(), Case::Sensitive),
+        vec![
+            (".gitattributes".into(), id_of_stage(0)),
+            ("conflict/.gitattributes".into(), id_of_stage(2)),
+        ],
+        "stage 0 is preferred over stage 2, and conflicts use 'our' side, with one mapping per path"
+    );
+}
+
+#[test]
+fn for_status_reads_ignore_files_of_sparse_directories_from_the_index() {
+    let mut index = gix_index::State::new(gix_hash::Kind::Sha1);
+    let id = hex_to_id("5c7e0ed672d3d31d83a3df61f13cc8f7b22d5bfd");
+    for (path, flags) in [
+        ("dense/.gitignore", gix_index::entry::Flags::empty()),
+        (
+            "sparse/.gitignore",
+            gix_index::entry::Flags::SKIP_WORKTREE | gix_index::entry::Flags::EXTENDED,
+        ),
+    ] {
+        index.dangerously_push_entry(Default::default(), id, flags, gix_index::entry::Mode::FILE, path.into());
+    }
+    let state = gix_worktree::stack::State::for_status(
+        gix_worktree::stack::state::Ignore::new(
+            Default::default(),
+            Default::default(),
+            None,
+            Source::WorktreeThenIdMappingIfNotSkipped,
+        ),
+        None,
+    );
+    assert_eq!(
+        state.id_mappings_from_index(&index, index.path_backing(), Case::Sensitive),
+        vec![("sparse/.gitignore".into(), id)],
+        "only files that can't be read from the worktree due to being skip-worktree are read from the index"
+    );
+}
+
+#[test]
+fn for_status_reads_ignore_files_lazily_and_only_once() -> crate::Result {
+    let (tmp, odb, index) = index_with_blobs(&[
+        (".gitignore", b"ignored/\n"),
+        ("ignored/.gitignore", b"!keep\n"),
+        ("ignored/sub/.gitignore", b"!*\n"),
+        ("included/.gitignore", b"*.o\n"),
+    ])?;
+    let state = gix_worktree::stack::State::for_status(
+        gix_worktree::stack::state::Ignore::new(Default::default(), Default::default(), None, Source::IdMapping),
+        None,
+    );
+    let mut cache = Stack::from_state_and_ignore_case(tmp.path(), false, state, &index, index.path_backing());
+
+    for (relative_path, is_dir, expected_pattern) in [
+        ("included/a.o", Some(false), "*.o"),
+        ("ignored", Some(true), "ignored/"),
+        ("included/b.o", Some(false), "*.o"),
+    ] {
+        let m = cache
+            .matching_exclude_pattern(relative_path, is_dir, &odb)?
+            .expect("excluded");
+        assert_eq!(m.pattern.to_string(), expected_pattern, "{relative_path}");
+    }
+
+    let stats = cache.statistics().ignore;
+    assert_eq!(
+        stats.patterns_buffers, 2,
+        "only the ignore files of the root and of the included directory are read, \
+         while those of the excluded directory aren't as a walk doesn't have to enter it"
+    );
+    assert_eq!(
+        stats.cached_patterns_buffers, 1,
+        "the ignore file of the included directory is read only once even though it's entered twice"
+    );
+    Ok(())
+}
+
+#[test]
+fn for_status_reads_ignore_files_of_entered_directories_and_forgets_them_once_their_parent_is_left() -> crate::Result {
+    let (tmp, odb, index) = index_with_blobs(&[
+        (".gitignore", b"*.a\n"),
+        ("dir/.gitignore", b"*.o\n"),
+        ("dir/sub/.gitignore", b"*.so\n"),
+    ])?;
+    let state = gix_worktree::stack::State::for_status(
+        gix_worktree::stack::state::Ignore::new(Default::default(), Default::default(), None, Source::IdMapping),
+        None,
+    );
+    let mut cache = Stack::from_state_and_ignore_case(tmp.path(), false, state, &index, index.path_backing());
+
+    cache.push_directory("dir", &odb)?;
+    cache.push_directory("dir/sub", &odb)?;
+    let m = cache
+        .matching_exclude_pattern("dir/sub/lib.so", Some(false), &odb)?
+        .expect("excluded");
+    assert_eq!(m.source, Some(std::path::Path::new("dir/sub/.gitignore")));
+    assert_eq!(cache.statistics().ignore.patterns_buffers, 3);
+
+    cache.pop_directory();
+    cache.push_directory("dir/sub", &odb)?;
+    let stats = cache.statistics().ignore;
+    assert_eq!(stats.patterns_buffers, 3);
+    assert_eq!(
+        stats.cached_patterns_buffers, 1,
+        "entering a directory again uses the patterns that were kept when it was left"
+    );
+
+    cache.pop_directory();
+    cache.pop_directory();
+    cache.push_directory("dir", &odb)?;
+    cache.push_directory("dir/sub", &odb)?;
+    let stats = cache.statistics().ignore;
+    assert_eq!(
+        stats.cached_patterns_buffers, 2,
+        "the directory itself is still cached…"
+    );
+    assert_eq!(
+        stats.patterns_buffers, 4,
+        "…but its subdirectories were forgotten when it was left, so they are read again"
+    );
+
+    let m = cache
+        .matching_exclude_pattern("dir/file.o", Some(false), &odb)?
+        .expect("excluded");
+    assert_eq!(m.source, Some(std::path::Path::new("dir/.gitignore")));
+    Ok(())
+}
+
+#[test]
+fn for_status_matches_files_that_are_ignored_and_skip_worktree() -> crate::Result {
+    let (tmp, odb, mut index) = index_with_blobs(&[("sparse/.gitignore", b"*.o\n"), ("sparse/file.o", b"")])?;
+    let worktree = tmp.path().join("worktree");
+    for entry in index.entries_mut() {
+        entry.flags |= gix_index::entry::Flags::SKIP_WORKTREE | gix_index::entry::Flags::EXTENDED;
+    }
+    let state = gix_worktree::stack::State::for_status(
+        gix_worktree::stack::state::Ignore::new(
+            Default::default(),
+            Default::default(),
+            None,
+            Source::WorktreeThenIdMappingIfNotSkipped,
+        ),
+        None,
+    );
+    let mut cache = Stack::from_state_and_ignore_case(&worktree, false, state, &index, index.path_backing());
+
+    let m = cache
+        .matching_exclude_pattern("sparse/file.o", Some(false), &odb)?
+        .expect("matched by the ignore file of the sparse directory");
+    assert_eq!(m.pattern.to_string(), "*.o");
+    assert_eq!(
+        m.source,
+        Some(std::path::Path::new("sparse/.gitignore")),
+        "the ignore file isn't in the worktree, so it's read from the index, \
+         while it's up to the caller to never consider tracked files ignored"
+    );
+    assert_eq!(cache.statistics().ignore.patterns_buffers, 1);
+    Ok(())
+}
+
+#[test]
+fn for_status_never_uses_ignore_files_outside_of_the_sparse_checkout() -> crate::Result {
+    let (tmp, odb, index) = index_with_blobs(&[
+        (".gitignore", b"*.a\n"),
+        ("included/.gitignore", b"*.o\n"),
+        ("excluded/.gitignore", b"*.o\n"),
+    ])?;
+    let state = gix_worktree::stack::State::for_status(
+        gix_worktree::stack::state::Ignore::new(Default::default(), Default::default(), None, Source::IdMapping),
+        Some(std::sync::Arc::new(|path: &BStr| !path.starts_with(b"excluded/"))),
+    );
+    assert_eq!(
+        state
+            .id_mappings_from_index(&index, index.path_backing(), Case::Sensitive)
+            .into_iter()
+            .map(|(path, _id)| path)
+            .collect::<Vec<_>>(),
+        [".gitignore", "included/.gitignore"],
+        "the ignore file outside of the sparse checkout isn't a source, even without the skip-worktree bit"
+    );
+    let mut cache = Stack::from_state_and_ignore_case(tmp.path(), false, state, &index, index.path_backing());
+
+    let m = cache
+        .matching_exclude_pattern("included/file.o", Some(false), &odb)?
+        .expect("matched by the ignore file of the included directory");
+    assert_eq!(m.source, Some(std::path::Path::new("included/.gitignore")));
+    assert!(
+        cache
+            .matching_exclude_pattern("excluded/file.o", Some(false), &odb)?
+            .is_none(),
+        "the patterns of the excluded directory are never read"
+    );
+    assert_eq!(cache.statistics().ignore.patterns_buffers, 2);
+    Ok(())
+}
+
+#[test]
+fn submodules_are_opaque_directories() -> crate::Result {
+    use gix_odb::Write;
+
+    let case = probe_case()?;
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let odb = gix_odb::at(tmp.path())?;
+    let commit_id = hex_to_id("5c7e0ed672d3d31d83a3df61f13cc8f7b22d5bfd");
+    let mut index = gix_index::State::new(gix_hash::Kind::Sha1);
+    for (path, content) in [
+        (".gitattributes", Some(&b"sub attr\n"[..])),
+        (".gitignore", Some(b"sub/\n")),
+        ("other/.gitattributes", None),
+        ("sub", None),
+    ] {
+        let (id, mode) = match content {
+            Some(content) => (
+                odb.write_buf(gix_object::Kind::Blob, content)?,
+                gix_index::entry::Mode::FILE,
+            ),
+            None => (commit_id, gix_index::entry::Mode::COMMIT),
+        };
+        index.dangerously_push_entry(
+            Default::default(),
+            id,
+            gix_index::entry::Flags::empty(),
+            mode,
+            path.into(),
+        );
+    }
+    let state = gix_worktree::stack::State::AttributesAndIgnoreStack {
+        attributes: Default::default(),
+        ignore: gix_worktree::stack::state::Ignore::new(
+            Default::default(),
+            Default::default(),
+            None,
+            Source::IdMapping,
+        ),
+    };
+    let id_mappings = state.id_mappings_from_index(&index, index.path_backing(), case);
+    assert_eq!(
+        id_mappings.iter().map(|t| t.0.to_str_lossy()).collect::<Vec<_>>(),
+        [".gitattributes", ".gitignore"],
+        "files next to submodules are used, but a submodule is never an attribute file, whatever its name"
+    );
+
+    let mut cache = Stack::new(tmp.path(), state, case, Vec::new(), id_mappings);
+    let mut attributes = cache.attribute_matches();
+    for submodule in index.entries().iter().filter(|e| e.mode.is_submodule()) {
+        let path = submodule.path(&index);
+        let platform = cache.at_entry(path, Some(submodule.mode.is_submodule()), &odb)?;
+        assert_eq!(
+            platform.is_excluded(),
+            path == "sub",
+            "{path}: submodules are matched as directories"
+        );
+        assert_eq!(
+            platform.matching_attributes(&mut attributes),
+            path == "sub",
+            "{path}: attributes apply to the submodule itself"
+        );
+    }
+    assert!(
+        !cache.at_entry("sub", Some(false), &odb)?.is_excluded(),
+        "declaring the submodule a file doesn't match directory patterns"
+    );
+    Ok(())
+}
+
+#[test]
+fn ignore_and_attribute_files_in_the_same_directory_keep_their_kind() -> crate::Result {
+    let (tmp, odb, index) = index_with_blobs(&[
+        (".gitattributes", b"*.attr from-attributes\n"),
+        (".gitignore", b"*.ignored\n"),
+        ("dir/.GITIGNORE", b"*.upper\n"),
+        ("dir/.GitAttributes", b"*.upper from-upper\n"),
+    ])?;
+    let state = gix_worktree::stack::State::AttributesAndIgnoreStack {
+        attributes: Default::default(),
+        ignore: gix_worktree::stack::state::Ignore::new(
+            Default::default(),
+            Default::default(),
+            None,
+            Source::IdMapping,
+        ),
+    };
+    let paths = |case| {
+        state
+            .id_mappings_from_index(&index, index.path_backing(), case)
+            .into_iter()
+            .map(|t| t.0.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(paths(Case::Sensitive), [".gitattributes", ".gitignore"]);
+    assert_eq!(
+        paths(Case::Fold),
+        [".gitattributes", ".gitignore", "dir/.GITIGNORE", "dir/.GitAttributes"],
+        "each file matches one of the names when ignoring case"
+    );
+
+    let case = Case::Fold;
+    let id_mappings = state.id_mappings_from_index(&index, index.path_backing(), case);
+    let mut cache = Stack::new(tmp.path(), state, case, Vec::new(), id_mappings);
+    let mut attributes = cache.attribute_matches();
+    for (path, expected_ignore_source, expected_attribute) in [
+        ("a.ignored", Some(".gitignore"), None),
+        ("a.attr", None, Some(".gitattributes")),
+    ] {
+        let platform = cache.at_entry(path, Some(false), &odb)?;
+        assert_eq!(
+            platform
+                .matching_exclude_pattern()
+                .and_then(|m| m.source.map(|p| p.to_string_lossy().into_owned())),
+            expected_ignore_source.map(ToOwned::to_owned),
+            "{path}: exclude patterns only come from the ignore file"
+        );
+        platform.matching_attributes(&mut attributes);
+        assert_eq!(
+            attributes
+                .iter()
+                .filter(|m| !m.assignment.state.is_unspecified())
+                .map(|m| m.location.source.expect("from file").to_string_lossy().into_owned())
+                .next()
+                .as_deref(),
+            expected_attribute,
+            "{path}: attributes only come from the attributes file"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn byte_order_marks_and_crlf_line_endings_are_tolerated() -> crate::Result {
+    let (tmp, odb, index) = index_with_blobs(&[("sub/.gitignore", "\u{feff}*.log\r\n".as_bytes())])?;
+    let id_mappings = id_mappings(&index);
+
+    let worktree = tmp.path().join("worktree");
+    std::fs::create_dir_all(worktree.join("sub"))?;
+    std::fs::write(worktree.join(".gitignore"), "\u{feff}*.o\r\n!keep.o\r\nbuild/\r\n")?;
+
+    let state = gix_worktree::stack::State::for_status(
+        gix_worktree::stack::state::Ignore::new(
+            Default::default(),
+            Default::default(),
+            None,
+            Source::WorktreeThenIdMappingIfNotSkipped,
+        ),
+        None,
+    );
+    let mut cache = Stack::new(&worktree, state, Case::Sensitive, Vec::new(), id_mappings);
+
+    for (rela_path, is_dir, expected) in [
+        ("a.o", None, Some(("*.o", 1))),
+        ("keep.o", None, Some(("!keep.o", 2))),
+        ("build", Some(true), Some(("build/", 3))),
+        ("sub/x.log", None, Some(("*.log", 1))),
+        ("x.log", None, None),
+    ] {
+        let platform = cache.at_entry(rela_path, is_dir, &odb)?;
+        let actual = platform
+            .matching_exclude_pattern()
+            .map(|m| (m.pattern.to_string(), m.sequence_number));
+        assert_eq!(
+            actual,
+            expected.map(|(pattern, line)| (pattern.to_owned(), line)),
+            "{rela_path}: the BOM isn't part of the first pattern, and CR isn't part of any pattern, no matter the source"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn would_ignore_works_on_paths_that_do_not_exist() {
+    let mut globals = gix_ignore::Search::default();
+    globals.add_patterns_buffer(b"# comment\n*.log\n!keep.log\nbuild/", "path/to/.gitignore", None);
+    let state = gix_worktree::stack::State::IgnoreStack(gix_worktree::stack::state::Ignore::new(
+        Default::default(),
+        globals,
+        None,
+        Source::IdMapping,
+    ));
+
+    let m = state
+        .would_ignore("does/not/exist.log".into(), Some(false), Case::Sensitive)
+        .expect("matched");
+    assert_eq!(m.pattern.to_string(), "*.log");
+    assert_eq!(m.source, Some(std::path::Path::new("path/to/.gitignore")));
+    assert_eq!(m.sequence_number, 2, "the line number of the pattern");
+
+    let m = state
+        .would_ignore("missing/keep.log".into(), Some(false), Case::Sensitive)
+        .expect("matched");
+    assert!(m.pattern.is_negative(), "negative patterns are returned as well");
+    assert_eq!(m.sequence_number, 3);
+
+    assert_eq!(
+        state
+            .would_ignore("missing/build".into(), Some(true), Case::Sensitive)
+            .map(|m| m.sequence_number),
+        Some(4),
+        "directory patterns match if the path is declared to be a directory"
+    );
+    assert_eq!(
+        state.would_ignore("missing/build".into(), Some(false), Case::Sensitive),
+        None,
+        "but not if it's a file"
+    );
+    assert_eq!(
+        state
+            .would_ignore("missing/build/".into(), None, Case::Sensitive)
+            .map(|m| m.sequence_number),
+        Some(4),
+        "a trailing slash marks a directory"
+    );
+    assert_eq!(
+        state.would_ignore("missing/build".into(), None, Case::Sensitive),
+        None,
+        "and without it, it's assumed to be a file"
+    );
+    assert_eq!(
+        state
+            .would_ignore("missing/BUILD/".into(), None, Case::Fold)
+            .map(|m| m.sequence_number),
+        Some(4),
+        "the slash isn't part of the case-insensitive comparison"
+    );
+    assert_eq!(
+        gix_worktree::stack::State::AttributesStack(Default::default()).would_ignore(
+            "a.log".into(),
+            None,
+            Case::Sensitive
+        ),
+        None,
+        "without exclude information, nothing is ever excluded"
+    );
+}
+
+#[test]
+fn custom_exclude_file_names_are_used_for_the_index_and_the_worktree() -> crate::Result {
+    let case = probe_case()?;
+    let (tmp, odb, index) = index_with_blobs(&[
+        ("index/.customignore", b"*.custom-index\n"),
+        ("index/.gitattributes", b"* attr\n"),
+        ("index/.gitignore", b"*.git-index\n"),
+    ])?;
+    let worktree = tmp.path().join("worktree");
+    std::fs::create_dir_all(worktree.join("wt"))?;
+    std::fs::write(worktree.join("wt/.customignore"), "*.custom-wt\n")?;
+    std::fs::write(worktree.join("wt/.gitignore"), "*.git-wt\n")?;
+
+    for source in [Source::IdMapping, Source::WorktreeThenIdMappingIfNotSkipped] {
+        let state = gix_worktree::stack::State::AttributesAndIgnoreStack {
+            attributes: Default::default(),
+            ignore: gix_worktree::stack::state::Ignore::new(
+                Default::default(),
+                Default::default(),
+                Some(".customignore".into()),
+                source,
+            ),
+        };
+        let id_mappings = state.id_mappings_from_index(&index, index.path_backing(), case);
+        let expected_paths: &[&str] = match source {
+            Source::IdMapping => &["index/.customignore", "index/.gitattributes"],
+            Source::WorktreeThenIdMappingIfNotSkipped => &["index/.gitattributes"],
+        };
+        assert_eq!(
+            id_mappings.iter().map(|t| t.0.to_str_lossy()).collect::<Vec<_>>(),
+            expected_paths,
+            "{source:?}: attribute files are still picked up, but ignore files only by their custom name"
+        );
+
+        let mut cache = Stack::new(&worktree, state, case, Vec::new(), id_mappings);
+        let mut attributes = cache.attribute_matches();
+        assert!(
+            cache
+                .at_entry("index/file", Some(false), &odb)?
+                .matching_attributes(&mut attributes),
+            "{source:?}: attributes are read as usual"
+        );
+        for (path, excluded) in [
+            ("index/a.custom-index", matches!(source, Source::IdMapping)),
+            ("index/a.git-index", false),
+            ("wt/a.custom-wt", !matches!(source, Source::IdMapping)),
+            ("wt/a.git-wt", false),
+        ] {
+            assert_eq!(
+                cache.at_entry(path, Some(false), &odb)?.is_excluded(),
+                excluded,
+                "{source:?}: {path} is only excluded by files of the custom name"
+            );
+        }
+    }
+    Ok(())
+}
@@ -21,3 +21,39 @@ fn probe_case() -> crate::Result<Case> {
         },
     )
 }
+
+/// Write `files` as blobs into a new object database and add them to an index sorted by path, for reading attribute
+/// and ignore files from the object database.
+/// The returned directory contains the object database along with an empty `worktree` directory.
+fn index_with_blobs(
+    files: &[(&str, &[u8])],
+) -> crate::Result<(gix_testtools::tempfile::TempDir, gix_odb::Handle, gix_index::State)> {
+    use gix_odb::Write;
+
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let objects_dir = tmp.path().join("objects");
+    std::fs::create_dir(&objects_dir)?;
+    std::fs::create_dir(tmp.path().join("worktree"))?;
+    let odb = gix_odb::at(&objects_dir)?;
+    let mut index = gix_index::State::new(gix_hash::Kind::Sha1);
+    for (path, content) in files {
+        index.dangerously_push_entry(
+            Default::default(),
+            odb.write_buf(gix_object::Kind::Blob, content)?,
+            gix_index::entry::Flags::empty(),
+            gix_index::entry::Mode::FILE,
+            (*path).into(),
+        );
+    }
+    index.sort_entries();
+    Ok((tmp, odb, index))
+}
+
+/// Return the path and id of all entries in `index`, to use them as id mappings as is.
+fn id_mappings(index: &gix_index::State) -> Vec<(bstr::BString, gix_hash::ObjectId)> {
+    index
+        .entries()
+        .iter()
+        .map(|entry| (entry.path(index).to_owned(), entry.id))
+        .collect()
+}
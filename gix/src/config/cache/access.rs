@@ -309,6 +309,7 @@ impl Cache {
             thread_limit,
             destination_is_initially_empty: false,
             overwrite_existing: false,
+            skip_identical_files: false,
             keep_going: false,
             stat_options: self.stat_options().map_err(|err| match err {
                 config::stat_options::Error::ConfigCheckStat(err) => {
@@ -146,6 +146,13 @@ impl Cache {
         let reflog = util::query_refupdates(&config, lenient_config)?;
         let refs_namespace = util::query_refs_namespace(&config, lenient_config)?;
         let ignore_case = config_bool(&config, &Core::IGNORE_CASE, "core.ignoreCase", false, lenient_config)?;
+        let precompose_unicode = config_bool(
+            &config,
+            &Core::PRECOMPOSE_UNICODE,
+            "core.precomposeUnicode",
+            false,
+            lenient_config,
+        )?;
         let use_multi_pack_index = config_bool(
             &config,
             &Core::MULTIPACK_INDEX,
@@ -171,6 +178,7 @@ impl Cache {
             refs_namespace,
             is_bare,
             ignore_case,
+            precompose_unicode,
             hex_len,
             filter_config_section,
             environment,
@@ -218,6 +226,13 @@ impl Cache {
             false,
             self.lenient_config,
         )?;
+        let precompose_unicode = config_bool(
+            config,
+            &Core::PRECOMPOSE_UNICODE,
+            "core.precomposeUnicode",
+            false,
+            self.lenient_config,
+        )?;
 
         #[cfg(feature = "revision")]
         {
@@ -229,6 +244,7 @@ impl Cache {
 
         self.hex_len = hex_len;
         self.ignore_case = ignore_case;
+        self.precompose_unicode = precompose_unicode;
         self.reflog = reflog;
         self.refs_namespace = refs_namespace;
 
@@ -592,10 +608,6 @@ fn apply_environment_overrides(
                 let key = &Core::SSH_COMMAND;
                 (env(key), key.name, git_prefix)
             },
-            {
-                let key = &Core::USE_REPLACE_REFS;
-                (env(key), key.name, objects)
-            },
         ] {
             if let Some(value) = var_as_bstring(var, permission) {
                 section.push_with_comment(
@@ -606,6 +618,19 @@ fn apply_environment_overrides(
             }
         }
 
+        {
+            // Like in `git`, the mere presence of the variable disables replacements, no matter its value.
+            let key = &Core::USE_REPLACE_REFS;
+            let var = env(key);
+            if var_as_bstring(var, objects).is_some() {
+                section.push_with_comment(
+                    key.name.try_into().expect("statically known to be valid"),
+                    Some("false".into()),
+                    format!("from {var}").as_str(),
+                );
+            }
+        }
+
         if section.num_values() == 0 {
             let id = section.id();
             env_override.remove_section_by_id(id);
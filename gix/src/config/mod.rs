@@ -626,13 +626,14 @@ pub(crate) struct Cache {
     pub object_kind_hint: Option<crate::revision::spec::parse::ObjectKindHint>,
     /// If true, we are on a case-insensitive file system.
     pub ignore_case: bool,
+    /// If true, we are on a file system that decomposes unicode, so paths should be precomposed to be compared.
+    pub precompose_unicode: bool,
     /// If true, we should default what's possible if something is misconfigured, on case by case basis, to be more resilient.
     /// Also available in options! Keep in sync!
     pub lenient_config: bool,
     #[cfg_attr(not(feature = "worktree-mutation"), allow(dead_code))]
     attributes: crate::open::permissions::Attributes,
     environment: crate::open::permissions::Environment,
-    // TODO: make core.precomposeUnicode available as well.
 }
 
 /// Utilities shared privately across the crate, for lack of a better place.
@@ -11,6 +11,10 @@ impl RevSpecExt for gix_revision::Spec {
             path: None,
             first_ref: None,
             second_ref: None,
+            blob_size: None,
+            ref_candidates: Default::default(),
+            ref_chain: Vec::new(),
+            warnings: Vec::new(),
             repo,
         }
     }
@@ -36,13 +36,16 @@ impl<'repo> Object<'repo> {
     ///
     /// Note that this object doesn't necessarily have to be the end of the chain.
     /// Typical values are [`Kind::Commit`] or [`Kind::Tree`].
+    ///
+    /// If `kind` is [`Kind::Tag`], the first tag encountered is returned without following it further, which is the object
+    /// itself if it is a tag. As commits, trees and blobs can't be peeled into tags, this fails for anything else.
     pub fn peel_to_kind(mut self, kind: Kind) -> Result<Self, peel::to_kind::Error> {
         loop {
             match self.kind {
                 our_kind if kind == our_kind => {
                     return Ok(self);
                 }
-                Kind::Commit => {
+                Kind::Commit if kind != Kind::Tag => {
                     let tree_id = self
                         .try_to_commit_ref_iter()
                         .expect("commit")
@@ -58,7 +61,7 @@ impl<'repo> Object<'repo> {
                     drop(self);
                     self = repo.find_object(target_id)?;
                 }
-                Kind::Tree | Kind::Blob => {
+                Kind::Commit | Kind::Tree | Kind::Blob => {
                     return Err(peel::to_kind::Error::NotFound {
                         oid: self.id().shorten().unwrap_or_else(|_| self.id.into()),
                         actual: self.kind,
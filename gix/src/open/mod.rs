@@ -18,7 +18,8 @@ pub struct Permissions {
 /// ### Replacement Objects for the object database
 ///
 /// The environment variables `GIT_REPLACE_REF_BASE` and `GIT_NO_REPLACE_OBJECTS` are mapped to `gitoxide.objects.replaceRefBase`
-/// and `gitoxide.objects.noReplace` respectively and then interpreted exactly as their environment variable counterparts.
+/// and `core.useReplaceRefs` respectively and then interpreted exactly as their environment variable counterparts.
+/// Replacements are used unless `core.useReplaceRefs` is `false`, which is also the case if `GIT_NO_REPLACE_OBJECTS` is set to any value.
 ///
 /// Use [Permissions] to control which environment variables can be read, and config-overrides to control these values programmatically.
 #[derive(Clone)]
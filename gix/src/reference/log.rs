@@ -18,6 +18,37 @@ impl<'repo> Reference<'repo> {
     pub fn log_exists(&self) -> bool {
         self.inner.log_exists(&self.repo.refs)
     }
+
+    /// Return the `n`-th entry of the reflog counted from the oldest one, where `1` is the oldest entry, or `None` if
+    /// there is no reflog, if it has fewer than `n` entries or if `n` is `0`.
+    ///
+    /// The [new value](gix_ref::log::Line::new_oid) of the oldest entry is the value this reference was created with,
+    /// unless the reflog was truncated.
+    /// Note that there is no rev-spec for this, as `git` rejects `<ref>@{-n}`.
+    pub fn log_entry_from_oldest(&self, n: usize) -> Result<Option<gix_ref::log::Line>, oldest_entry::Error> {
+        let Some(index) = n.checked_sub(1) else {
+            return Ok(None);
+        };
+        let mut platform = self.log_iter();
+        let Some(mut lines) = platform.all()? else {
+            return Ok(None);
+        };
+        Ok(lines.nth(index).transpose()?.map(Into::into))
+    }
+}
+
+///
+#[allow(clippy::empty_docs)]
+pub mod oldest_entry {
+    /// The error returned by [`Reference::log_entry_from_oldest()`](crate::Reference::log_entry_from_oldest()).
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The reflog could not be read")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Decode(#[from] gix_ref::file::log::iter::decode::Error),
+    }
 }
 
 /// Generate a message typical for git commit logs based on the given `operation`, commit `message` and `num_parents` of the commit.
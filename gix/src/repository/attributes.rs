@@ -46,7 +46,12 @@ impl Repository {
             self.config
                 .assemble_exclude_globals(self.git_dir(), exclude_overrides, ignore_source, &mut buf)?;
         let state = gix_worktree::stack::State::AttributesAndIgnoreStack { attributes, ignore };
-        let attribute_list = state.id_mappings_from_index(index, index.path_backing(), case);
+        let attribute_list = state.id_mappings_from_index_with_precompose_unicode(
+            index,
+            index.path_backing(),
+            case,
+            self.config.precompose_unicode,
+        );
         Ok(AttributeStack::new(
             gix_worktree::Stack::new(
                 // this is alright as we don't cause mutation of that directory, it's virtual.
@@ -78,7 +83,46 @@ impl Repository {
             self.options.permissions.attributes,
         )?;
         let state = gix_worktree::stack::State::AttributesStack(attributes);
-        let attribute_list = state.id_mappings_from_index(index, index.path_backing(), case);
+        let attribute_list = state.id_mappings_from_index_with_precompose_unicode(
+            index,
+            index.path_backing(),
+            case,
+            self.config.precompose_unicode,
+        );
+        Ok(AttributeStack::new(
+            gix_worktree::Stack::new(
+                // this is alright as we don't cause mutation of that directory, it's virtual.
+                self.work_dir().unwrap_or(self.git_dir()),
+                state,
+                case,
+                buf,
+                attribute_list,
+            ),
+            self,
+        ))
+    }
+
+    /// Like [attributes_only()][Self::attributes_only()], but obtains all `.gitattributes` files from the `tree` with
+    /// the given id instead of from an index or the worktree, at any depth.
+    ///
+    /// This is useful to obtain attributes as they were in any historical tree, and works without index and worktree.
+    #[cfg(feature = "attributes")]
+    pub fn attributes_only_from_tree(
+        &self,
+        tree: &gix_hash::oid,
+    ) -> Result<AttributeStack<'_>, crate::repository::attributes_only_from_tree::Error> {
+        let case = if self.config.ignore_case {
+            gix_glob::pattern::Case::Fold
+        } else {
+            gix_glob::pattern::Case::Sensitive
+        };
+        let (attributes, buf) = self.config.assemble_attribute_globals(
+            self.git_dir(),
+            gix_worktree::stack::state::attributes::Source::IdMapping,
+            self.options.permissions.attributes,
+        )?;
+        let state = gix_worktree::stack::State::AttributesStack(attributes);
+        let attribute_list = state.id_mappings_from_tree(tree, &self.objects, case)?;
         Ok(AttributeStack::new(
             gix_worktree::Stack::new(
                 // this is alright as we don't cause mutation of that directory, it's virtual.
@@ -123,7 +167,12 @@ impl Repository {
             .config
             .assemble_exclude_globals(self.git_dir(), overrides, source, &mut buf)?;
         let state = gix_worktree::stack::State::IgnoreStack(ignore);
-        let attribute_list = state.id_mappings_from_index(index, index.path_backing(), case);
+        let attribute_list = state.id_mappings_from_index_with_precompose_unicode(
+            index,
+            index.path_backing(),
+            case,
+            self.config.precompose_unicode,
+        );
         Ok(AttributeStack::new(
             gix_worktree::Stack::new(
                 // this is alright as we don't cause mutation of that directory, it's virtual.
@@ -55,15 +55,7 @@ impl crate::Repository {
                     Err(err) => return Some(Err(err.into())),
                 };
                 if remote.push_specs.is_empty() {
-                    let push_default = match self
-                        .config
-                        .resolved
-                        .string(Push.name(), None, Push::DEFAULT.name)
-                        .map_or(Ok(Default::default()), |v| {
-                            Push::DEFAULT
-                                .try_into_default(v)
-                                .with_lenient_default(self.config.lenient_config)
-                        }) {
+                    let push_default = match self.push_default() {
                         Ok(v) => v,
                         Err(err) => return Some(Err(err.into())),
                     };
@@ -97,6 +89,8 @@ impl crate::Repository {
     ///   `refs/heads/*:refs/remotes/origin/*` fetch refspec, hence `refs/remotes/origin/main` is returned.
     ///   Note that `push` refspecs can be used to map `main` to `other` (using a push refspec `refs/heads/main:refs/heads/other`),
     ///   which would then lead to `refs/remotes/origin/other` to be returned instead.
+    ///   Without push refspecs and with `push.default = upstream`, the upstream tracking branch as obtained with
+    ///   [remote::Direction::Fetch] is returned, even if the push remote is different, just like Git does.
     ///
     /// Note that if there is an ambiguity, that is if `name` maps to multiple tracking branches, the first matching mapping
     /// is returned, according to the order in which the fetch or push refspecs occur in the configuration file.
@@ -117,6 +111,16 @@ impl crate::Repository {
             Err(err) => return Some(Err(err.into())),
         };
 
+        if direction == remote::Direction::Push && remote.push_specs.is_empty() {
+            match self.push_default() {
+                Ok(push::Default::Upstream) => {
+                    return self.branch_remote_tracking_ref_name(name, remote::Direction::Fetch)
+                }
+                Ok(_) => {}
+                Err(err) => return Some(Err(branch_remote_ref_name::Error::from(err).into())),
+            }
+        }
+
         if remote.fetch_specs.is_empty() {
             return None;
         }
@@ -124,6 +128,17 @@ impl crate::Repository {
             .map(|res| res.map_err(Into::into))
     }
 
+    fn push_default(&self) -> Result<push::Default, crate::config::key::GenericErrorWithValue> {
+        self.config
+            .resolved
+            .string(Push.name(), None, Push::DEFAULT.name)
+            .map_or(Ok(Default::default()), |v| {
+                Push::DEFAULT
+                    .try_into_default(v)
+                    .with_lenient_default(self.config.lenient_config)
+            })
+    }
+
     /// Returns the unvalidated name of the remote associated with the given `short_branch_name`,
     /// typically `main` instead of `refs/heads/main`.
     /// In some cases, the returned name will be an URL.
@@ -121,6 +121,20 @@ pub mod pathspec_defaults_ignore_case {
     }
 }
 
+///
+#[cfg(feature = "attributes")]
+pub mod attributes_only_from_tree {
+    /// The error returned by [`Repository::attributes_only_from_tree()`][crate::Repository::attributes_only_from_tree()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        ConfigureAttributes(#[from] crate::config::attribute_stack::Error),
+        #[error(transparent)]
+        FindTree(#[from] gix_object::find::existing_object::Error),
+    }
+}
+
 ///
 #[cfg(feature = "index")]
 pub mod index_or_load_from_head {
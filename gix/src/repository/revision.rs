@@ -1,6 +1,9 @@
 use gix_macros::momo;
 
-use crate::{bstr::BStr, revision, Id};
+use crate::{
+    bstr::{BStr, ByteSlice},
+    revision, Id,
+};
 
 /// Methods for resolving revisions by spec or working with the commit graph.
 impl crate::Repository {
@@ -35,6 +38,49 @@ impl crate::Repository {
             .ok_or(revision::spec::parse::single::Error::RangedRev { spec: spec.into() })
     }
 
+    /// Return all branches, remote branches and tags whose name starts with `partial`, for completing a partially typed
+    /// revision in a shell or an interactive tool.
+    ///
+    /// `partial` is matched against short names like `main` or `origin/main`, against full names like `refs/heads/main`,
+    /// and against full names without `refs/`, like `heads/main`, in that order, so each candidate completes to the form
+    /// that was started.
+    /// Candidates are ordered by their full name, and references that can't be read are skipped.
+    #[momo]
+    pub fn rev_parse_ref_completions<'a>(
+        &self,
+        partial: impl Into<&'a BStr>,
+    ) -> Result<Vec<revision::spec::parse::RefCompletion>, revision::spec::parse::Error> {
+        use gix_ref::Category;
+        let partial = partial.into();
+        let mut out: Vec<_> = self
+            .references()?
+            .all()?
+            .filter_map(Result::ok)
+            .filter_map(|r| {
+                let name = r.detach().name;
+                let (category, short_name) = name.category_and_short_name()?;
+                let category = match category {
+                    Category::LocalBranch => Category::LocalBranch,
+                    Category::RemoteBranch => Category::RemoteBranch,
+                    Category::Tag => Category::Tag,
+                    _ => return None,
+                };
+                let full_name = name.as_bstr();
+                let completion = [short_name, full_name, full_name["refs/".len()..].as_bstr()]
+                    .into_iter()
+                    .find(|candidate| candidate.starts_with(partial))?
+                    .to_owned();
+                Some(revision::spec::parse::RefCompletion {
+                    name,
+                    category,
+                    completion,
+                })
+            })
+            .collect();
+        out.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(out)
+    }
+
     /// Create the baseline for a revision walk by initializing it with the `tips` to start iterating on.
     ///
     /// It can be configured further before starting the actual walk.
@@ -30,5 +30,14 @@ pub struct Spec<'repo> {
     pub(crate) first_ref: Option<gix_ref::Reference>,
     /// The second name of a reference as seen while parsing a `RevSpec`, for completeness.
     pub(crate) second_ref: Option<gix_ref::Reference>,
+    /// The size of the blob this spec resolved to, if it was requested and the spec is a single blob.
+    pub(crate) blob_size: Option<u64>,
+    /// All references a reference name could have referred to, for the first and second reference respectively, if there was
+    /// more than one.
+    pub(crate) ref_candidates: [Vec<gix_ref::Reference>; 2],
+    /// All references consulted while resolving the spec, in order, if requested.
+    pub(crate) ref_chain: Vec<gix_ref::Reference>,
+    /// Non-fatal issues encountered while resolving the spec.
+    pub(crate) warnings: Vec<spec::parse::Warning>,
     pub(crate) repo: &'repo crate::Repository,
 }
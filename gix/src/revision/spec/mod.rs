@@ -1,5 +1,14 @@
-use crate::bstr::BStr;
-use crate::{ext::ReferenceExt, revision::Spec, Id, Reference};
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use gix_hash::ObjectId;
+
+use crate::bstr::{BStr, BString};
+use crate::{
+    ext::{ObjectIdExt, ReferenceExt},
+    revision,
+    revision::Spec,
+    Id, Reference,
+};
 
 ///
 #[allow(clippy::empty_docs)]
@@ -33,6 +42,19 @@ mod impls {
     impl<'repo> Eq for Spec<'repo> {}
 }
 
+/// A plain-data projection of a [`Spec`] without its repository, suitable as key for caching or deduplicating resolved specs.
+///
+/// Obtain it with [`Spec::key()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key {
+    /// The objects the spec resolved to.
+    pub spec: gix_revision::Spec,
+    /// The name of the first reference encountered while resolving the spec, if any.
+    pub first_ref: Option<gix_ref::FullName>,
+    /// The name of the second reference encountered while resolving the spec, if any.
+    pub second_ref: Option<gix_ref::FullName>,
+}
+
 /// Initialization
 impl<'repo> Spec<'repo> {
     /// Create a single specification which points to `id`.
@@ -43,6 +65,10 @@ impl<'repo> Spec<'repo> {
             repo: id.repo,
             first_ref: None,
             second_ref: None,
+            blob_size: None,
+            ref_candidates: Default::default(),
+            ref_chain: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 }
@@ -54,6 +80,19 @@ impl<'repo> Spec<'repo> {
         self.inner
     }
 
+    /// Return a key that identifies this spec by the objects it resolved to and the names of its first and second reference,
+    /// ignoring the repository it belongs to, for use in maps or sets that cache or deduplicate resolved specs.
+    ///
+    /// For example, `HEAD` and `@` produce the same key, whereas `HEAD` and the id it points to don't as only the former
+    /// involves a reference.
+    pub fn key(&self) -> Key {
+        Key {
+            spec: self.inner,
+            first_ref: self.first_ref.as_ref().map(|r| r.name.clone()),
+            second_ref: self.second_ref.as_ref().map(|r| r.name.clone()),
+        }
+    }
+
     /// Some revision specifications leave information about references which are returned as `(from-ref, to-ref)` here, e.g.
     /// `HEAD@{-1}..main` might be `(Some(refs/heads/previous-branch), Some(refs/heads/main))`,
     /// or `@` returns `(Some(refs/heads/main), None)`.
@@ -85,6 +124,106 @@ impl<'repo> Spec<'repo> {
         self.second_ref.as_ref()
     }
 
+    /// Return all references that the names of the first and second reference could have referred to, respectively, or
+    /// empty slices if the name was unambiguous or [`Options::ref_candidates`][crate::revision::spec::parse::Options::ref_candidates]
+    /// wasn't set.
+    ///
+    /// For example, `origin` might yield `refs/heads/origin` and `refs/remotes/origin/HEAD` as candidates, in the order
+    /// in which `git` would consider them, with the first one being the one that was used.
+    pub fn ref_candidates(&self) -> (&[gix_ref::Reference], &[gix_ref::Reference]) {
+        (&self.ref_candidates[0], &self.ref_candidates[1])
+    }
+
+    /// Return all references that were consulted while resolving the spec, in the order in which they were looked up, or
+    /// an empty slice if [`Options::ref_chain`][crate::revision::spec::parse::Options::ref_chain] wasn't set.
+    ///
+    /// For example, `@{-1}..main` might yield `HEAD`, whose reflog was used to find the previously checked out branch,
+    /// followed by `refs/heads/previous-branch` and `refs/heads/main`.
+    /// Unlike [`first_reference()`](Self::first_reference()) and [`second_reference()`](Self::second_reference()),
+    /// this also contains references that were only used to find others, like the branch of `main@{upstream}`.
+    pub fn ref_chain(&self) -> &[gix_ref::Reference] {
+        &self.ref_chain
+    }
+
+    /// Return the size of the blob this spec resolved to in bytes, as obtained from its object header.
+    ///
+    /// It's only available if [`Options::blob_size`][crate::revision::spec::parse::Options::blob_size] was set and
+    /// the spec resolved to a single blob, and `None` otherwise.
+    pub fn blob_size(&self) -> Option<u64> {
+        self.blob_size
+    }
+
+    /// Return a canonical textual form of this spec, like `main..origin/main`, `^v1.0^{commit}` or `a^!`, for logging
+    /// and display.
+    ///
+    /// The short names of the first and second reference are used if they still point to the respective object,
+    /// followed by `^{<kind>}` if they had to be peeled to get there. Otherwise, the object is named by its short id.
+    /// Paths like in `main:file` are never named as the spec refers to the object at the path.
+    ///
+    /// Note that the result parses to the same spec, unless references changed in the mean time.
+    pub fn name(&self) -> BString {
+        use gix_revision::Spec::*;
+        let first = |id| self.endpoint_name(self.first_ref.as_ref(), id);
+        match self.inner {
+            Include(id) => first(id),
+            Exclude(id) => format!("^{}", first(id)).into(),
+            Range { from, to } => {
+                format!("{}..{}", first(from), self.endpoint_name(self.second_ref.as_ref(), to)).into()
+            }
+            Merge { theirs, ours } => format!(
+                "{}...{}",
+                first(theirs),
+                self.endpoint_name(self.second_ref.as_ref(), ours)
+            )
+            .into(),
+            IncludeOnlyParents(id) => format!("{}^@", first(id)).into(),
+            ExcludeParents(id) => format!("{}^!", first(id)).into(),
+            ExcludeOnlyParents(id) => format!("^{}^@", first(id)).into(),
+        }
+    }
+
+    fn endpoint_name(&self, reference: Option<&gix_ref::Reference>, id: ObjectId) -> BString {
+        let repo = self.repo;
+        if let Some(reference) = reference.filter(|_| self.path.is_none()) {
+            let name = reference.name.shorten();
+            match reach(repo, reference, id) {
+                Some(Reach::Directly) => return name.into(),
+                Some(Reach::Peeled) => {
+                    if let Ok(header) = repo.find_header(id) {
+                        return format!("{name}^{{{}}}", header.kind()).into();
+                    }
+                }
+                None => {}
+            }
+        }
+        id.attach(repo).shorten_or_id().to_string().into()
+    }
+
+    /// Return all non-fatal issues that were encountered while resolving the spec, for the caller to display as they see fit.
+    ///
+    /// For example, `main@{1979-02-26}` yields a warning if the reflog of `main` doesn't go back that far.
+    pub fn warnings(&self) -> &[parse::Warning] {
+        &self.warnings
+    }
+
+    /// Return `true` if this spec is a range like `a..b`, which includes what's reachable from `b` but not from `a`.
+    pub fn is_range(&self) -> bool {
+        matches!(self.inner, gix_revision::Spec::Range { .. })
+    }
+
+    /// Return `true` if this spec is a merge-base spec like `a...b`, which includes what's reachable from either side,
+    /// but not from both.
+    pub fn is_merge(&self) -> bool {
+        matches!(self.inner, gix_revision::Spec::Merge { .. })
+    }
+
+    /// Return `true` if this spec names a single object, which is when [`single_object()`][Self::single_object()] succeeds.
+    ///
+    /// Note that this includes exclusions like `^a` and `a^@`, for which [`single()`][Self::single()] returns `None`.
+    pub fn is_single(&self) -> bool {
+        !self.is_range() && !self.is_merge()
+    }
+
     /// Return the single included object represented by this instance, or `None` if it is a range of any kind.
     pub fn single(&self) -> Option<Id<'repo>> {
         match self.inner {
@@ -94,7 +233,340 @@ impl<'repo> Spec<'repo> {
             gix_revision::Spec::Exclude(_)
             | gix_revision::Spec::Range { .. }
             | gix_revision::Spec::Merge { .. }
-            | gix_revision::Spec::IncludeOnlyParents { .. } => None,
+            | gix_revision::Spec::IncludeOnlyParents { .. }
+            | gix_revision::Spec::ExcludeOnlyParents { .. } => None,
+        }
+    }
+
+    /// Return the single object represented by this instance, or fail with [`RangeNotAllowed`][parse::Error::RangeNotAllowed]
+    /// if it is a range or merge-base.
+    ///
+    /// Unlike [`single()`][Self::single()], this also returns the object of exclusions like `^rev` and of `rev^@`,
+    /// which is useful for callers that expect a single revision and want to reject ranges instead of silently using
+    /// only one of their endpoints. Use [`Id::detach()`] to obtain the plain object id.
+    pub fn single_object(&self) -> Result<Id<'repo>, parse::Error> {
+        match self.inner {
+            gix_revision::Spec::Include(id)
+            | gix_revision::Spec::Exclude(id)
+            | gix_revision::Spec::ExcludeParents(id)
+            | gix_revision::Spec::IncludeOnlyParents(id)
+            | gix_revision::Spec::ExcludeOnlyParents(id) => Ok(Id::from_id(id, self.repo)),
+            gix_revision::Spec::Range { .. } | gix_revision::Spec::Merge { .. } => {
+                Err(parse::Error::RangeNotAllowed { spec: self.inner })
+            }
+        }
+    }
+
+    /// Like [`single_object()`][Self::single_object()], but also lookup the object in the object database and return it.
+    ///
+    /// Ranges and merge-bases fail with [`RangeNotAllowed`][parse::Error::RangeNotAllowed], and objects that can't be found
+    /// fail with [`FindObject`][parse::Error::FindObject].
+    pub fn object(&self) -> Result<crate::Object<'repo>, parse::Error> {
+        Ok(self.single_object()?.object()?)
+    }
+
+    /// Return the reference this spec resolved to, for callers that want to operate on the reference itself, for instance
+    /// to update it, instead of the object it points to.
+    ///
+    /// Fail with [`NotAReference`][parse::Error::NotAReference] if no reference was used, like with a short hash, or if the
+    /// reference was only used to find another object, like in `main~1` or `main:file`. Tags may be peeled though,
+    /// so `v1.0^{commit}` still yields `v1.0`.
+    /// Ranges and merge-bases fail with [`RangeNotAllowed`][parse::Error::RangeNotAllowed].
+    pub fn single_reference(&self) -> Result<&gix_ref::Reference, parse::Error> {
+        let id = self.single_object()?.detach();
+        self.first_ref
+            .as_ref()
+            .filter(|reference| self.path.is_none() && reach(self.repo, reference, id).is_some())
+            .ok_or(parse::Error::NotAReference { spec: self.inner })
+    }
+}
+
+/// How a reference gets to an object.
+enum Reach {
+    /// The reference points to the object, possibly after following symbolic references.
+    Directly,
+    /// The reference points to the object only after peeling tags.
+    Peeled,
+}
+
+/// Return how `reference` gets to `id`, or `None` if it doesn't.
+fn reach(repo: &crate::Repository, reference: &gix_ref::Reference, id: ObjectId) -> Option<Reach> {
+    let mut r = reference.clone().attach(repo);
+    // Follow symbolic references like `HEAD` without peeling tags, as limited as in `git` to not loop forever.
+    for _ in 0..5 {
+        match r.follow() {
+            Some(Ok(next)) => r = next,
+            _ => break,
+        }
+    }
+    if r.try_id().map(Id::detach) == Some(id) {
+        Some(Reach::Directly)
+    } else if r.into_fully_peeled_id().ok().map(Id::detach) == Some(id) {
+        Some(Reach::Peeled)
+    } else {
+        None
+    }
+}
+
+/// Traversal
+impl<'repo> Spec<'repo> {
+    /// Return an iterator over all commits this spec describes, like `git rev-list <spec>` would list them.
+    ///
+    /// For instance, `a..b` yields the commits reachable from `b` but not from `a`, and `a...b` yields the commits
+    /// reachable from either side, but not from both. Excluding specs like `^a` yield nothing.
+    ///
+    /// Commits to exclude are discovered along the way by walking the history of the [hidden tips](Self::hidden_tips())
+    /// only down to the age of the commit at hand, so the walk stops as soon as all commits that are still to be visited
+    /// are hidden. Just like `git rev-list`, this assumes that commits aren't older than their parents.
+    pub fn commits(&self) -> Result<revision::Walk<'repo>, revision::walk::Error> {
+        let hidden = Rc::new(RefCell::new(Hidden::new(self.repo, self.hidden_tips()?)?));
+        let mut walk = self
+            .repo
+            .rev_walk(self.tips()?)
+            .selected({
+                let hidden = hidden.clone();
+                move |id| !hidden.borrow_mut().contains(id)
+            })?
+            .inner;
+        Ok(revision::Walk {
+            repo: self.repo,
+            inner: Box::new(std::iter::from_fn(move || {
+                let next = walk.next();
+                match hidden.borrow_mut().error.take() {
+                    Some(err) => Some(Err(err)),
+                    None => next,
+                }
+            })),
+        })
+    }
+
+    /// Return the commits that this spec includes, for use as starting points of a revision walk which then also
+    /// yields their ancestors, unless they are reachable from the [hidden tips](Self::hidden_tips()).
+    ///
+    /// For instance, `a` and `a..b` start at `a` and `b` respectively, `a...b` starts at both sides, and `a^@` starts
+    /// at all parents of `a`, but not at `a` itself.
+    /// Specs that don't include anything, like `^a` or `^a^@`, yield an empty list.
+    pub fn tips(&self) -> Result<Vec<ObjectId>, revision::walk::Error> {
+        use gix_revision::Spec::*;
+        Ok(match self.inner {
+            Include(id) | Range { to: id, .. } | ExcludeParents(id) => vec![id],
+            Merge { theirs, ours } => vec![theirs, ours],
+            IncludeOnlyParents(id) => parent_ids(self.repo, id)?,
+            Exclude(_) | ExcludeOnlyParents(_) => Vec::new(),
+        })
+    }
+
+    /// Return the commits that this spec excludes, for use as hidden starting points of a revision walk which then
+    /// also hides all of their ancestors.
+    ///
+    /// For instance, `^a` and `a..b` hide `a`, whereas `a^!` and `^a^@` hide all parents of `a`, and `a...b` hides
+    /// the [merge-bases](Self::merge_bases()) of `a` and `b`.
+    /// Specs that don't exclude anything, like `a` or `a^@`, yield an empty list.
+    pub fn hidden_tips(&self) -> Result<Vec<ObjectId>, revision::walk::Error> {
+        use gix_revision::Spec::*;
+        Ok(match self.inner {
+            Include(_) | IncludeOnlyParents(_) => Vec::new(),
+            Exclude(id) | Range { from: id, .. } => vec![id],
+            ExcludeParents(id) | ExcludeOnlyParents(id) => parent_ids(self.repo, id)?,
+            Merge { .. } => self
+                .merge_bases()?
+                .expect("merges have merge-bases")
+                .into_iter()
+                .map(Id::detach)
+                .collect(),
+        })
+    }
+
+    /// Return all merge-bases of both sides of a merge spec like `a...b`, or `None` if this isn't a merge spec.
+    ///
+    /// Merge-bases are the best common ancestors of both sides, i.e. those that aren't an ancestor of another common
+    /// ancestor, just like `git merge-base --all a b` lists them.
+    /// There may be more than one of them, for instance after criss-cross merges, or none at all if both sides
+    /// have unrelated histories.
+    ///
+    /// The walk stops as soon as all commits that are still to be visited are known to be ancestors of a common
+    /// ancestor, and uses generation numbers of the commit-graph if present, so typically only the part of both
+    /// histories since the merge-bases is traversed.
+    pub fn merge_bases(&self) -> Result<Option<Vec<Id<'repo>>>, revision::walk::Error> {
+        let gix_revision::Spec::Merge { theirs, ours } = self.inner else {
+            return Ok(None);
+        };
+        let mut graph = self.repo.revision_graph();
+        let mut bases = merge_base::paint_down_to_common(&mut graph, ours, &[theirs])?;
+        if bases.len() > 1 {
+            bases = merge_base::remove_redundant(&mut graph, bases)?;
+        }
+        Ok(Some(bases.into_iter().map(|id| id.attach(self.repo)).collect()))
+    }
+}
+
+/// An implementation of `git merge-base --all` on top of a [revision graph](gix_revwalk::Graph), modeled after
+/// `paint_down_to_common()` and `remove_redundant()` in the git codebase.
+mod merge_base {
+    use gix_hash::ObjectId;
+    use gix_revwalk::{graph, PriorityQueue};
+
+    type Flags = u8;
+    type Graph<'find> = gix_revwalk::Graph<'find, graph::Commit<Flags>>;
+    /// Commits are visited by generation first, and by commit time if generations are equal or unknown.
+    type Queue = PriorityQueue<(u32, gix_date::SecondsSinceUnixEpoch), ObjectId>;
+
+    /// Reachable from the first commit.
+    const PARENT1: Flags = 1 << 0;
+    /// Reachable from any of the second commits.
+    const PARENT2: Flags = 1 << 1;
+    /// Reachable from a common ancestor, and thus not a best common ancestor itself.
+    const STALE: Flags = 1 << 2;
+    /// A common ancestor that was already collected.
+    const RESULT: Flags = 1 << 3;
+
+    /// Return all common ancestors of `first` and `second` that aren't reachable from a common ancestor found earlier,
+    /// which are all merge-bases unless clock skew lets older commits be visited first.
+    pub(super) fn paint_down_to_common(
+        graph: &mut Graph<'_>,
+        first: ObjectId,
+        second: &[ObjectId],
+    ) -> Result<Vec<ObjectId>, graph::try_lookup_or_insert_default::Error> {
+        graph.clear();
+        if second.contains(&first) {
+            return Ok(vec![first]);
+        }
+        let mut queue = Queue::new();
+        for (id, flags) in std::iter::once((first, PARENT1)).chain(second.iter().map(|id| (*id, PARENT2))) {
+            if let Some(commit) = graph.try_lookup_or_insert_commit(id, |data| *data |= flags)? {
+                queue.insert(key(commit), id);
+            }
+        }
+
+        let mut out = Vec::new();
+        while queue
+            .iter_unordered()
+            .any(|id| matches!(graph.get(id), Some(commit) if commit.data & STALE == 0))
+        {
+            let Some(id) = queue.pop_value() else { break };
+            let commit = graph.get_mut(&id).expect("queued commits are in the graph");
+            let mut flags = commit.data & (PARENT1 | PARENT2 | STALE);
+            if flags == (PARENT1 | PARENT2) {
+                if commit.data & RESULT == 0 {
+                    commit.data |= RESULT;
+                    out.push(id);
+                }
+                flags |= STALE;
+            }
+            for parent_id in commit.parents.clone() {
+                let mut was_updated = false;
+                if let Some(parent) = graph.try_lookup_or_insert_commit(parent_id, |data| {
+                    was_updated = *data & flags != flags;
+                    *data |= flags;
+                })? {
+                    if was_updated {
+                        queue.insert(key(parent), parent_id);
+                    }
+                }
+            }
+        }
+        // Common ancestors that were reached from other common ancestors after they were found aren't the best ones.
+        out.retain(|id| !matches!(graph.get(id), Some(commit) if commit.data & STALE != 0));
+        Ok(out)
+    }
+
+    /// Remove all `candidates` that are reachable from another candidate.
+    pub(super) fn remove_redundant(
+        graph: &mut Graph<'_>,
+        candidates: Vec<ObjectId>,
+    ) -> Result<Vec<ObjectId>, graph::try_lookup_or_insert_default::Error> {
+        let mut redundant = vec![false; candidates.len()];
+        for (idx, candidate) in candidates.iter().enumerate() {
+            if redundant[idx] {
+                continue;
+            }
+            let others: Vec<_> = candidates
+                .iter()
+                .enumerate()
+                .filter(|(other_idx, _)| *other_idx != idx && !redundant[*other_idx])
+                .map(|(_, id)| *id)
+                .collect();
+            paint_down_to_common(graph, *candidate, &others)?;
+            if matches!(graph.get(candidate), Some(commit) if commit.data & PARENT2 != 0) {
+                redundant[idx] = true;
+            }
+            for (other_idx, other) in candidates.iter().enumerate() {
+                if other_idx != idx && matches!(graph.get(other), Some(commit) if commit.data & PARENT1 != 0) {
+                    redundant[other_idx] = true;
+                }
+            }
         }
+        Ok(candidates
+            .into_iter()
+            .zip(redundant)
+            .filter_map(|(id, redundant)| (!redundant).then_some(id))
+            .collect())
     }
+
+    fn key(commit: &graph::Commit<Flags>) -> (u32, gix_date::SecondsSinceUnixEpoch) {
+        (commit.generation.unwrap_or(u32::MAX), commit.commit_time)
+    }
+}
+
+/// The commits reachable from hidden tips, discovered lazily and newest first.
+struct Hidden<'repo> {
+    repo: &'repo crate::Repository,
+    walk: std::iter::Peekable<revision::Walk<'repo>>,
+    ids: HashSet<ObjectId>,
+    buf: Vec<u8>,
+    /// The first error encountered while walking hidden commits, to be returned by the walk of commits to show.
+    error: Option<gix_traverse::commit::ancestors::Error>,
+}
+
+impl<'repo> Hidden<'repo> {
+    fn new(repo: &'repo crate::Repository, tips: Vec<ObjectId>) -> Result<Self, revision::walk::Error> {
+        Ok(Hidden {
+            repo,
+            walk: repo
+                .rev_walk(tips)
+                .sorting(gix_traverse::commit::Sorting::ByCommitTimeNewestFirst)
+                .all()?
+                .peekable(),
+            ids: HashSet::new(),
+            buf: Vec::new(),
+            error: None,
+        })
+    }
+
+    /// Return `true` if `id` is reachable from a hidden tip, walking hidden commits only until they are older than `id`.
+    fn contains(&mut self, id: &gix_hash::oid) -> bool {
+        use gix_object::FindExt;
+        let commit_time = self
+            .repo
+            .objects
+            .find_commit_iter(id, &mut self.buf)
+            .ok()
+            .and_then(|commit| commit.committer().ok().map(|committer| committer.time.seconds))
+            .unwrap_or(gix_date::SecondsSinceUnixEpoch::MIN);
+        while let Some(info) = self
+            .walk
+            .next_if(|info| !matches!(info, Ok(info) if info.commit_time() < commit_time))
+        {
+            match info {
+                Ok(info) => {
+                    self.ids.insert(info.id);
+                }
+                Err(err) => {
+                    self.error.get_or_insert(err);
+                    break;
+                }
+            }
+        }
+        self.ids.contains(id)
+    }
+}
+
+fn parent_ids(repo: &crate::Repository, id: ObjectId) -> Result<Vec<ObjectId>, revision::walk::Error> {
+    use gix_object::FindExt;
+    let mut buf = Vec::new();
+    let commit = repo
+        .objects
+        .find_commit_iter(&id, &mut buf)
+        .map_err(gix_traverse::commit::ancestors::Error::from)?;
+    Ok(commit.parent_ids().collect())
 }
@@ -4,39 +4,61 @@ use gix_hash::ObjectId;
 use gix_revision::spec::{parse, parse::delegate};
 use smallvec::SmallVec;
 
-use super::{Delegate, Error, ObjectKindHint};
+use super::{Delegate, Error, FetchMissingObject, ObjectKindHint};
 use crate::{
+    bstr::BStr,
     ext::{ObjectIdExt, ReferenceExt},
     Repository,
 };
 
 type Replacements = SmallVec<[(ObjectId, ObjectId); 1]>;
 
-impl<'repo> Delegate<'repo> {
+impl<'repo> Delegate<'repo, '_, '_> {
+    /// Create a new instance to resolve revisions in `repo`, configured by `opts`.
     pub fn new(repo: &'repo Repository, opts: crate::revision::spec::parse::Options) -> Self {
         Delegate {
             refs: Default::default(),
+            ref_candidates: Default::default(),
+            ref_chain: Vec::new(),
             objs: Default::default(),
             paths: Default::default(),
             ambiguous_objects: Default::default(),
             idx: 0,
             kind: None,
+            left_endpoint_failed: false,
+            tree_origins: Default::default(),
             err: Vec::new(),
+            warnings: Vec::new(),
             prefix: Default::default(),
             last_call_was_disambiguate_prefix: Default::default(),
             opts,
+            index: None,
+            fetch: None,
             repo,
         }
     }
 
+    /// Return all errors accumulated so far, without the ambiguity errors that [`into_err()`](Self::into_err()) adds.
+    pub fn errors(&self) -> &[Error] {
+        &self.err
+    }
+
+    /// Return `true` if errors were recorded and the current endpoint has no object left to continue with,
+    /// which means that parsing can't succeed anymore.
+    pub fn has_fatal(&self) -> bool {
+        !self.errors().is_empty() && self.objs[self.idx].as_ref().map_or(true, HashSet::is_empty)
+    }
+
+    /// Turn all errors accumulated so far, along with errors about ambiguous objects, into a single error.
     pub fn into_err(mut self) -> Error {
         let repo = self.repo;
+        let summarize = self.opts.ambiguity_summaries;
         for err in self
             .ambiguous_objects
             .iter_mut()
             .zip(self.prefix)
             .filter_map(|(a, b)| a.take().filter(|candidates| candidates.len() > 1).zip(b))
-            .map(|(candidates, prefix)| Error::ambiguous(candidates, prefix, repo))
+            .map(|(candidates, prefix)| Error::ambiguous(candidates, prefix, repo, summarize))
             .rev()
         {
             self.err.insert(0, err);
@@ -44,12 +66,14 @@ impl<'repo> Delegate<'repo> {
         Error::from_errors(self.err)
     }
 
+    /// Turn what was parsed into a [`Spec`](crate::revision::Spec), or fail if it doesn't resolve unambiguously.
     pub fn into_rev_spec(mut self) -> Result<crate::revision::Spec<'repo>, Error> {
         fn zero_or_one_objects_or_ambiguity_err(
             mut candidates: [Option<HashSet<ObjectId>>; 2],
             prefix: [Option<gix_hash::Prefix>; 2],
             mut errors: Vec<Error>,
             repo: &Repository,
+            summarize: bool,
         ) -> Result<[Option<ObjectId>; 2], Error> {
             let mut out = [None, None];
             for ((candidates, prefix), out) in candidates.iter_mut().zip(prefix).zip(out.iter_mut()) {
@@ -67,7 +91,12 @@ impl<'repo> Delegate<'repo> {
                             _ => {
                                 errors.insert(
                                     0,
-                                    Error::ambiguous(candidates, prefix.expect("set when obtaining candidates"), repo),
+                                    Error::ambiguous(
+                                        candidates,
+                                        prefix.expect("set when obtaining candidates"),
+                                        repo,
+                                        summarize,
+                                    ),
                                 );
                                 return Err(Error::from_errors(errors));
                             }
@@ -96,22 +125,49 @@ impl<'repo> Delegate<'repo> {
                 },
                 IncludeReachableFromParents => gix_revision::Spec::IncludeOnlyParents(first.ok_or(Error::Malformed)?),
                 ExcludeReachableFromParents => gix_revision::Spec::ExcludeParents(first.ok_or(Error::Malformed)?),
+                ExcludeReachableFromParentsOnly => {
+                    gix_revision::Spec::ExcludeOnlyParents(first.ok_or(Error::Malformed)?)
+                }
             })
         }
 
-        let range = zero_or_one_objects_or_ambiguity_err(self.objs, self.prefix, self.err, self.repo)?;
+        if self.left_endpoint_failed {
+            return Err(self.into_err());
+        }
+        let range = zero_or_one_objects_or_ambiguity_err(
+            self.objs,
+            self.prefix,
+            self.err,
+            self.repo,
+            self.opts.ambiguity_summaries,
+        )?;
+        let inner = kind_to_spec(self.kind, range)?;
+        let blob_size = match inner {
+            gix_revision::Spec::Include(id) if self.opts.blob_size => {
+                let header = self.repo.find_header(id)?;
+                (header.kind() == gix_object::Kind::Blob).then(|| header.size())
+            }
+            _ => None,
+        };
         Ok(crate::revision::Spec {
             path: self.paths[0].take().or(self.paths[1].take()),
             first_ref: self.refs[0].take(),
             second_ref: self.refs[1].take(),
-            inner: kind_to_spec(self.kind, range)?,
+            inner,
+            blob_size,
+            ref_candidates: self.ref_candidates,
+            ref_chain: self.ref_chain,
+            warnings: self.warnings,
             repo: self.repo,
         })
     }
 }
 
-impl<'repo> parse::Delegate for Delegate<'repo> {
+impl<'repo> parse::Delegate for Delegate<'repo, '_, '_> {
     fn done(&mut self) {
+        if self.left_endpoint_failed {
+            return;
+        }
         self.follow_refs_to_objects_if_needed();
         self.disambiguate_objects_by_fallback_hint(
             self.kind_implies_committish()
@@ -121,10 +177,14 @@ impl<'repo> parse::Delegate for Delegate<'repo> {
     }
 }
 
-impl<'repo> delegate::Kind for Delegate<'repo> {
+impl<'repo> delegate::Kind for Delegate<'repo, '_, '_> {
     fn kind(&mut self, kind: gix_revision::spec::Kind) -> Option<()> {
         use gix_revision::spec::Kind::*;
         self.kind = Some(kind);
+        if self.left_endpoint_failed {
+            // Now we know if the failed endpoint is part of a range, so there is no need to look at the right one.
+            return (!self.kind_is_range()).then_some(());
+        }
 
         if self.kind_implies_committish() {
             self.disambiguate_objects_by_fallback_hint(ObjectKindHint::Committish.into());
@@ -137,7 +197,73 @@ impl<'repo> delegate::Kind for Delegate<'repo> {
     }
 }
 
-impl<'repo> Delegate<'repo> {
+impl<'repo> delegate::Revision for Delegate<'repo, '_, '_> {
+    fn find_ref(&mut self, name: &BStr) -> Option<()> {
+        self.defer_left_endpoint_failure(|this| this.find_ref(name))
+    }
+
+    fn disambiguate_prefix(&mut self, prefix: gix_hash::Prefix, hint: Option<delegate::PrefixHint<'_>>) -> Option<()> {
+        // Failing here isn't final, as the parser falls back to trying the name as reference.
+        if self.left_endpoint_failed {
+            return Some(());
+        }
+        self.disambiguate_prefix(prefix, hint)
+    }
+
+    fn reflog(&mut self, query: delegate::ReflogLookup) -> Option<()> {
+        self.defer_left_endpoint_failure(|this| this.reflog(query))
+    }
+
+    fn nth_checked_out_branch(&mut self, branch_no: usize) -> Option<()> {
+        self.defer_left_endpoint_failure(|this| this.nth_checked_out_branch(branch_no))
+    }
+
+    fn sibling_branch(&mut self, kind: delegate::SiblingBranch) -> Option<()> {
+        self.defer_left_endpoint_failure(|this| this.sibling_branch(kind))
+    }
+}
+
+impl<'repo> delegate::Navigate for Delegate<'repo, '_, '_> {
+    fn traverse(&mut self, kind: delegate::Traversal) -> Option<()> {
+        self.defer_left_endpoint_failure(|this| this.traverse(kind))
+    }
+
+    fn peel_until(&mut self, kind: delegate::PeelTo<'_>) -> Option<()> {
+        self.defer_left_endpoint_failure(|this| this.peel_until(kind))
+    }
+
+    fn find(&mut self, regex: &BStr, negated: bool) -> Option<()> {
+        self.defer_left_endpoint_failure(|this| this.find(regex, negated))
+    }
+
+    fn index_lookup(&mut self, path: &BStr, stage: u8) -> Option<()> {
+        self.defer_left_endpoint_failure(|this| this.index_lookup(path, stage))
+    }
+}
+
+impl<'repo> Delegate<'repo, '_, '_> {
+    /// Call `op`, and if it fails on the left endpoint, pretend it succeeded so that parsing continues up to the point where
+    /// [`kind()`](delegate::Kind::kind()) tells us if the endpoint belongs to a range.
+    /// Once failed, nothing is resolved anymore.
+    fn defer_left_endpoint_failure(&mut self, op: impl FnOnce(&mut Self) -> Option<()>) -> Option<()> {
+        if self.left_endpoint_failed {
+            return Some(());
+        }
+        let res = op(self);
+        if res.is_none() && self.idx == 0 {
+            self.left_endpoint_failed = true;
+            return Some(());
+        }
+        res
+    }
+
+    pub(super) fn kind_is_range(&self) -> bool {
+        matches!(
+            self.kind,
+            Some(gix_revision::spec::Kind::RangeBetween | gix_revision::spec::Kind::ReachableToMergeBase)
+        )
+    }
+
     fn kind_implies_committish(&self) -> bool {
         self.kind.unwrap_or(gix_revision::spec::Kind::IncludeReachable) != gix_revision::spec::Kind::IncludeReachable
     }
@@ -189,6 +315,12 @@ impl<'repo> Delegate<'repo> {
 
                 if errors.len() == objs.len() {
                     self.err.extend(errors.into_iter().map(|(_, err)| err));
+                } else if objs.len() - errors.len() == 1 {
+                    // Only one candidate qualifies, so there is no ambiguity to speak of, neither now nor in later errors.
+                    for (obj, _err) in errors {
+                        objs.remove(&obj);
+                    }
+                    self.ambiguous_objects[self.idx] = Some(objs.clone());
                 } else {
                     for (obj, err) in errors {
                         objs.remove(&obj);
@@ -219,6 +351,20 @@ impl<'repo> Delegate<'repo> {
     fn unset_disambiguate_call(&mut self) {
         self.last_call_was_disambiguate_prefix[self.idx] = false;
     }
+
+    fn record_ref(&mut self, r: &gix_ref::Reference) {
+        if self.opts.ref_chain {
+            self.ref_chain.push(r.clone());
+        }
+    }
+
+    fn record_head(&mut self) {
+        if self.opts.ref_chain {
+            if let Ok(head) = self.repo.refs.find("HEAD") {
+                self.ref_chain.push(head);
+            }
+        }
+    }
 }
 
 fn peel(repo: &Repository, obj: &gix_hash::oid, kind: gix_object::Kind) -> Result<ObjectId, Error> {
@@ -228,6 +374,41 @@ fn peel(repo: &Repository, obj: &gix_hash::oid, kind: gix_object::Kind) -> Resul
     Ok(obj.id)
 }
 
+/// Call `op` until it succeeds, and each time it fails because an object is missing, let `fetch` obtain it first.
+/// Give up if there is no `fetch`, if `op` fails for other reasons, or if the object is still missing after fetching it.
+fn retry_after_fetch<T>(
+    mut fetch: Option<&mut FetchMissingObject<'_>>,
+    mut op: impl FnMut() -> Result<T, Error>,
+) -> Result<T, Error> {
+    let mut fetched = Vec::new();
+    loop {
+        let err = match op() {
+            Ok(res) => return Ok(res),
+            Err(err) => err,
+        };
+        let (Some(fetch), Some(oid)) = (fetch.as_deref_mut(), missing_object(&err)) else {
+            return Err(err);
+        };
+        if fetched.contains(&oid) {
+            return Err(err);
+        }
+        fetch(&oid).map_err(|source| Error::FetchMissingObject { oid, source })?;
+        fetched.push(oid);
+    }
+}
+
+/// Return the id of the object that couldn't be found if this is why `err` occurred.
+fn missing_object(err: &Error) -> Option<ObjectId> {
+    use gix_object::find::existing::Error as FindError;
+    match err {
+        Error::FindObject(FindError::NotFound { oid })
+        | Error::PeelToKind(crate::object::peel::to_kind::Error::FindExistingObject(FindError::NotFound { oid })) => {
+            Some(*oid)
+        }
+        _ => None,
+    }
+}
+
 fn handle_errors_and_replacements(
     destination: &mut Vec<Error>,
     objs: &mut HashSet<ObjectId>,
@@ -1,37 +1,50 @@
-use std::collections::HashSet;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
 
 use gix_hash::ObjectId;
-use gix_revision::spec::parse::{
-    delegate,
-    delegate::{PeelTo, Traversal},
-};
+use gix_revision::spec::parse::delegate::{PeelTo, Traversal};
 use gix_traverse::commit::Sorting;
 
 use crate::{
-    bstr::{BStr, ByteSlice},
+    bstr::{BStr, BString, ByteSlice},
     ext::ObjectIdExt,
     object,
     revision::spec::parse::{
-        delegate::{handle_errors_and_replacements, peel, Replacements},
+        delegate::{handle_errors_and_replacements, peel, retry_after_fetch, Replacements},
         Delegate, Error,
     },
     Object,
 };
 
-impl<'repo> delegate::Navigate for Delegate<'repo> {
-    fn traverse(&mut self, kind: Traversal) -> Option<()> {
+impl<'repo> Delegate<'repo, '_, '_> {
+    pub(super) fn traverse(&mut self, kind: Traversal) -> Option<()> {
         self.unset_disambiguate_call();
         self.follow_refs_to_objects_if_needed()?;
 
         let mut replacements = Replacements::default();
         let mut errors = Vec::new();
         let objs = self.objs[self.idx].as_mut()?;
+        let tree_origins = &mut self.tree_origins[self.idx];
         let repo = self.repo;
+        let graph = matches!(kind, Traversal::NthAncestor(_))
+            .then(|| commit_graph(repo))
+            .flatten();
 
         for obj in objs.iter() {
+            let tree_origin = tree_origins.get(obj).copied();
+            let start = match committish(repo, tree_origin.unwrap_or(*obj)) {
+                Ok(start) => start,
+                Err(err) => {
+                    errors.push((*obj, err));
+                    continue;
+                }
+            };
+            let num_replacements = replacements.len();
             match kind {
                 Traversal::NthParent(num) => {
-                    match self.repo.find_object(*obj).map_err(Error::from).and_then(|obj| {
+                    match self.repo.find_object(start).map_err(Error::from).and_then(|obj| {
                         obj.try_into_commit().map_err(|err| {
                             let object::try_into::Error { actual, expected, id } = err;
                             Error::ObjectKind {
@@ -42,9 +55,9 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
                         })
                     }) {
                         Ok(commit) => match commit.parent_ids().nth(num.saturating_sub(1)) {
-                            Some(id) => replacements.push((commit.id, id.detach())),
+                            Some(id) => replacements.push((*obj, id.detach())),
                             None => errors.push((
-                                commit.id,
+                                *obj,
                                 Error::ParentOutOfRange {
                                     oid: commit.id().shorten_or_id(),
                                     desired: num,
@@ -55,31 +68,31 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
                         Err(err) => errors.push((*obj, err)),
                     }
                 }
-                Traversal::NthAncestor(num) => {
-                    let id = obj.attach(repo);
-                    match id
-                        .ancestors()
-                        .first_parent_only()
-                        .all()
-                        .expect("cannot fail without sorting")
-                        .skip(num)
-                        .find_map(Result::ok)
-                    {
-                        Some(commit) => replacements.push((*obj, commit.id)),
-                        None => errors.push((
-                            *obj,
-                            Error::AncestorOutOfRange {
-                                oid: id.shorten_or_id(),
-                                desired: num,
-                                available: id
-                                    .ancestors()
-                                    .first_parent_only()
-                                    .all()
-                                    .expect("cannot fail without sorting")
-                                    .skip(1)
-                                    .count(),
-                            },
-                        )),
+                Traversal::NthAncestor(num) => match nth_ancestor(repo, graph.as_ref(), start, num) {
+                    Ok(Ok(id)) => replacements.push((*obj, id)),
+                    Ok(Err(available)) => errors.push((
+                        *obj,
+                        Error::AncestorOutOfRange {
+                            oid: start.attach(repo).shorten_or_id(),
+                            desired: num,
+                            available,
+                        },
+                    )),
+                    Err(err) => errors.push((*obj, err)),
+                },
+            }
+            if tree_origin.is_some() {
+                // We navigated the commit the tree was obtained from, so turn the resulting commit back into its tree.
+                if let Some((_, commit_id)) = replacements.get_mut(num_replacements) {
+                    match peel(repo, commit_id, gix_object::Kind::Tree) {
+                        Ok(tree_id) => {
+                            tree_origins.insert(tree_id, *commit_id);
+                            *commit_id = tree_id;
+                        }
+                        Err(err) => {
+                            replacements.pop();
+                            errors.push((*obj, err));
+                        }
                     }
                 }
             }
@@ -88,7 +101,7 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
         handle_errors_and_replacements(&mut self.err, objs, errors, &mut replacements)
     }
 
-    fn peel_until(&mut self, kind: PeelTo<'_>) -> Option<()> {
+    pub(super) fn peel_until(&mut self, kind: PeelTo<'_>) -> Option<()> {
         self.unset_disambiguate_call();
         self.follow_refs_to_objects_if_needed()?;
 
@@ -96,46 +109,100 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
         let mut errors = Vec::new();
         let objs = self.objs[self.idx].as_mut()?;
         let repo = self.repo;
+        let mut fetch = if self.opts.fetch_missing_objects {
+            self.fetch.as_deref_mut()
+        } else {
+            None
+        };
 
         match kind {
             PeelTo::ValidObject => {
+                // Replacement objects are applied transparently by the object database, so like `git`, we keep the
+                // original id while all further navigation sees the content of the replacement.
                 for obj in objs.iter() {
-                    match repo.find_object(*obj) {
+                    match retry_after_fetch(fetch.as_deref_mut(), || Ok(repo.find_object(*obj)?.id)) {
                         Ok(_) => {}
                         Err(err) => {
-                            errors.push((*obj, err.into()));
+                            errors.push((*obj, err));
                         }
                     };
                 }
             }
             PeelTo::ObjectKind(kind) => {
-                let peel = |obj| peel(repo, obj, kind);
                 for obj in objs.iter() {
-                    match peel(obj) {
-                        Ok(replace) => replacements.push((*obj, replace)),
+                    match retry_after_fetch(fetch.as_deref_mut(), || peel(repo, obj, kind)) {
+                        Ok(replace) => {
+                            if kind == gix_object::Kind::Tree && self.opts.allow_tree_navigation {
+                                record_tree_origin(repo, &mut self.tree_origins[self.idx], obj, replace);
+                            }
+                            replacements.push((*obj, replace))
+                        }
                         Err(err) => errors.push((*obj, err)),
                     }
                 }
             }
             PeelTo::Path(path) => {
-                let lookup_path = |obj: &ObjectId| {
+                let path = match normalize_path(repo, path) {
+                    Ok(path) => path,
+                    Err(err) => {
+                        self.err.push(err);
+                        return None;
+                    }
+                };
+                let path = path.as_ref();
+                let lookup_path = |obj: &ObjectId, path: &BStr| {
                     let tree_id = peel(repo, obj, gix_object::Kind::Tree)?;
                     if path.is_empty() {
                         return Ok((tree_id, gix_object::tree::EntryKind::Tree.into()));
                     }
                     let mut tree = repo.find_object(tree_id)?.into_tree();
-                    let entry =
-                        tree.peel_to_entry_by_path(gix_path::from_bstr(path))?
-                            .ok_or_else(|| Error::PathNotFound {
+                    match tree.peel_to_entry_by_path(gix_path::from_bstr(path))? {
+                        // Like in `git`, a trailing slash requires the entry to be a tree.
+                        Some(entry) if path.ends_with(b"/") && !entry.mode().is_tree() => {
+                            Err(Error::PathComponentNotATree {
+                                path: path.into(),
+                                component: path.trim_end_with(|c| c == '/').into(),
+                                object: obj.attach(repo).shorten_or_id(),
+                                tree: tree_id.attach(repo).shorten_or_id(),
+                            })
+                        }
+                        Some(entry) => Ok((entry.object_id(), entry.mode())),
+                        None => Err(match non_tree_component(repo, tree_id, path)? {
+                            Some(component) => Error::PathComponentNotATree {
                                 path: path.into(),
+                                component,
                                 object: obj.attach(repo).shorten_or_id(),
                                 tree: tree_id.attach(repo).shorten_or_id(),
-                            })?;
-                    Ok((entry.object_id(), entry.mode()))
+                            },
+                            None => Error::PathNotFound {
+                                path: path.into(),
+                                object: obj.attach(repo).shorten_or_id(),
+                                tree: tree_id.attach(repo).shorten_or_id(),
+                            },
+                        }),
+                    }
                 };
+                // Unlike `git`, allow `HEAD:src^{tree}` to assert the kind of the entry at `src`, but only if there is no
+                // entry with that exact name as paths may contain anything.
+                let path_and_kind = path
+                    .strip_suffix(b"}")
+                    .and_then(|path| path.rfind(b"^{").map(|pos| path.split_at(pos)))
+                    .and_then(|(path, kind)| {
+                        gix_object::Kind::from_bytes(&kind[2..])
+                            .ok()
+                            .map(|kind| (path.as_bstr(), kind))
+                    });
                 for obj in objs.iter() {
-                    match lookup_path(obj) {
-                        Ok((replace, mode)) => {
+                    let mut res = retry_after_fetch(fetch.as_deref_mut(), || lookup_path(obj, path))
+                        .map(|(id, mode)| (path, id, mode));
+                    if let (Err(Error::PathNotFound { .. }), Some((path, kind))) = (&res, path_and_kind) {
+                        if let Ok((id, mode)) = retry_after_fetch(fetch.as_deref_mut(), || lookup_path(obj, path)) {
+                            res = retry_after_fetch(fetch.as_deref_mut(), || peel(repo, &id, kind))
+                                .map(|id| (path, id, mode));
+                        }
+                    }
+                    match res {
+                        Ok((path, replace, mode)) => {
                             if !path.is_empty() {
                                 // Technically this is letting the last one win, but so be it.
                                 self.paths[self.idx] = Some((path.to_owned(), mode));
@@ -148,9 +215,11 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
             }
             PeelTo::RecursiveTagObject => {
                 for oid in objs.iter() {
-                    match oid.attach(repo).object().and_then(Object::peel_tags_to_end) {
-                        Ok(obj) => replacements.push((*oid, obj.id)),
-                        Err(err) => errors.push((*oid, err.into())),
+                    match retry_after_fetch(fetch.as_deref_mut(), || {
+                        Ok(oid.attach(repo).object().and_then(Object::peel_tags_to_end)?.id)
+                    }) {
+                        Ok(id) => replacements.push((*oid, id)),
+                        Err(err) => errors.push((*oid, err)),
                     }
                 }
             }
@@ -159,10 +228,18 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
         handle_errors_and_replacements(&mut self.err, objs, errors, &mut replacements)
     }
 
-    fn find(&mut self, regex: &BStr, negated: bool) -> Option<()> {
+    pub(super) fn find(&mut self, regex: &BStr, negated: bool) -> Option<()> {
         self.unset_disambiguate_call();
         self.follow_refs_to_objects_if_needed()?;
 
+        // Keep the negation in errors as otherwise it would look like nothing matched the text itself.
+        let searched: BString = if negated {
+            let mut searched = BString::from("!-");
+            searched.extend_from_slice(regex);
+            searched
+        } else {
+            regex.into()
+        };
         #[cfg(not(feature = "revparse-regex"))]
         let matches = |message: &BStr| -> bool { message.contains_str(regex) ^ negated };
         #[cfg(feature = "revparse-regex")]
@@ -220,7 +297,7 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
                                 errors.push((
                                     *oid,
                                     Error::NoRegexMatch {
-                                        regex: regex.into(),
+                                        regex: searched.clone(),
                                         commits_searched: count,
                                         oid: oid.attach(repo).shorten_or_id(),
                                     },
@@ -279,7 +356,7 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
                                     Some(())
                                 } else {
                                     self.err.push(Error::NoRegexMatchAllRefs {
-                                        regex: regex.into(),
+                                        regex: searched,
                                         commits_searched: count,
                                     });
                                     None
@@ -304,50 +381,241 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
         }
     }
 
-    fn index_lookup(&mut self, path: &BStr, stage: u8) -> Option<()> {
+    pub(super) fn index_lookup(&mut self, path: &BStr, stage: u8) -> Option<()> {
         self.unset_disambiguate_call();
-        match self.repo.index() {
-            Ok(index) => match index.entry_by_path_and_stage(path, stage.into()) {
-                Some(entry) => {
-                    self.objs[self.idx]
-                        .get_or_insert_with(HashSet::default)
-                        .insert(entry.id);
-
-                    self.paths[self.idx] = Some((
-                        path.to_owned(),
-                        entry
-                            .mode
-                            .to_tree_entry_mode()
-                            .unwrap_or(gix_object::tree::EntryKind::Blob.into()),
-                    ));
-                    Some(())
+        let path = match normalize_path(self.repo, path) {
+            Ok(path) => path,
+            Err(err) => {
+                self.err.push(err);
+                return None;
+            }
+        };
+        let path = path.as_ref();
+        // Bare repositories usually don't have an index, but if they do, like `git` we use it.
+        let repo_index;
+        let index = match self.index {
+            Some(index) => index,
+            None => match self.repo.try_index() {
+                Ok(Some(index)) => {
+                    repo_index = index;
+                    &**repo_index
                 }
-                None => {
-                    let stage_hint = [0, 1, 2]
-                        .iter()
-                        .filter(|our_stage| **our_stage != stage)
-                        .find_map(|stage| {
-                            index
-                                .entry_index_by_path_and_stage(path, (*stage).into())
-                                .map(|_| (*stage).into())
-                        });
-                    let exists = self
-                        .repo
-                        .work_dir()
-                        .map_or(false, |root| root.join(gix_path::from_bstr(path)).exists());
-                    self.err.push(Error::IndexLookup {
-                        desired_path: path.into(),
-                        desired_stage: stage.into(),
-                        exists,
-                        stage_hint,
+                Ok(None) => {
+                    let desired_path = path.into();
+                    self.err.push(if self.repo.is_bare() {
+                        Error::NoWorktree { desired_path }
+                    } else {
+                        Error::NoIndex { desired_path }
                     });
-                    None
+                    return None;
+                }
+                Err(err) => {
+                    self.err.push(err.into());
+                    return None;
                 }
             },
-            Err(err) => {
-                self.err.push(err.into());
+        };
+        match index.entry_by_path_and_stage(path, stage.into()) {
+            Some(entry) => {
+                self.objs[self.idx]
+                    .get_or_insert_with(HashSet::default)
+                    .insert(entry.id);
+
+                self.paths[self.idx] = Some((
+                    path.to_owned(),
+                    entry
+                        .mode
+                        .to_tree_entry_mode()
+                        .unwrap_or(gix_object::tree::EntryKind::Blob.into()),
+                ));
+                Some(())
+            }
+            None => {
+                let stage_hint = [0, 1, 2]
+                    .iter()
+                    .filter(|our_stage| **our_stage != stage)
+                    .find_map(|stage| {
+                        index
+                            .entry_index_by_path_and_stage(path, (*stage).into())
+                            .map(|_| (*stage).into())
+                    });
+                let exists = self
+                    .repo
+                    .work_dir()
+                    .map_or(false, |root| root.join(gix_path::from_bstr(path)).exists());
+                self.err.push(Error::IndexLookup {
+                    desired_path: path.into(),
+                    desired_stage: stage.into(),
+                    exists,
+                    stage_hint,
+                });
                 None
             }
         }
     }
 }
+
+/// Remember that `tree` was obtained from `obj` if it is a commit or peels to one, to allow navigating it later.
+fn record_tree_origin(
+    repo: &crate::Repository,
+    tree_origins: &mut HashMap<ObjectId, ObjectId>,
+    obj: &ObjectId,
+    tree: ObjectId,
+) {
+    if let Some(commit_id) = tree_origins
+        .get(obj)
+        .copied()
+        .or_else(|| peel(repo, obj, gix_object::Kind::Commit).ok())
+    {
+        tree_origins.insert(tree, commit_id);
+    }
+}
+
+/// Turn `path` as given after `<rev>:` or `:` into a path relative to the root of the tree.
+///
+/// A leading `/` addresses the root of the tree, while paths starting with `./` or `../` (or being `.` or `..`) are relative
+/// to the current working directory within the worktree, like in `git`. If the current working directory is outside the worktree,
+/// or if there is no worktree, these are relative to the root as well.
+/// All other paths are returned unchanged as they are relative to the root already.
+///
+/// Note that `:/` without a revision introduces a commit message search and never reaches us as index path.
+fn normalize_path<'a>(repo: &crate::Repository, path: &'a BStr) -> Result<Cow<'a, BStr>, Error> {
+    if path.starts_with(b"/") {
+        return Ok(Cow::Borrowed(path.trim_start_with(|c| c == '/').as_bstr()));
+    }
+    let is_cwd_relative = [&b"."[..], b".."]
+        .iter()
+        .any(|dots| matches!(path.strip_prefix(*dots), Some(rest) if rest.is_empty() || rest.starts_with(b"/")));
+    if !is_cwd_relative {
+        return Ok(Cow::Borrowed(path));
+    }
+
+    let prefix = repo
+        .prefix()?
+        .map(|prefix| gix_path::to_unix_separators_on_windows(gix_path::into_bstr(prefix)).into_owned())
+        .unwrap_or_default();
+    let mut components: Vec<&[u8]> = Vec::new();
+    for component in prefix.split_str("/").chain(path.split_str("/")) {
+        match component {
+            b"" | b"." => {}
+            b".." => {
+                if components.pop().is_none() {
+                    return Err(Error::PathOutsideOfTree { path: path.into() });
+                }
+            }
+            component => components.push(component),
+        }
+    }
+    let mut normalized = BString::from(components.join(&b"/"[..]));
+    if path.ends_with(b"/") && !normalized.is_empty() {
+        normalized.push(b'/');
+    }
+    Ok(Cow::Owned(normalized))
+}
+
+/// Return the leading portion of `path` up to the first component that exists in the tree at `tree_id`, but isn't a tree
+/// even though there are more components to descend into, or `None` if there is no such component.
+fn non_tree_component(
+    repo: &crate::Repository,
+    tree_id: ObjectId,
+    path: &BStr,
+) -> Result<Option<crate::bstr::BString>, Error> {
+    let tree = repo.find_object(tree_id)?.into_tree();
+    let components: Vec<_> = path.split_str("/").filter(|c| !c.is_empty()).collect();
+    let mut buf = Vec::new();
+    for end in 1..components.len() {
+        match tree.lookup_entry(components[..end].iter().copied(), &mut buf)? {
+            Some(entry) if !entry.mode().is_tree() => return Ok(Some(components[..end].join(&b"/"[..]).into())),
+            Some(_) => continue,
+            None => break,
+        }
+    }
+    Ok(None)
+}
+
+/// Return the commit-graph of `repo` if it may be used and can be loaded, as it's merely a cache.
+fn commit_graph(repo: &crate::Repository) -> Option<gix_commitgraph::Graph> {
+    repo.config
+        .may_use_commit_graph()
+        .unwrap_or(true)
+        .then(|| repo.commit_graph().ok())
+        .flatten()
+}
+
+/// Return the commit `id` refers to, which is `id` itself or the commit that the tags starting at `id` point to,
+/// or fail with [`CannotTraverseNonCommit`](Error::CannotTraverseNonCommit) if it isn't a commit-ish, like a blob
+/// obtained with `HEAD:file`.
+fn committish(repo: &crate::Repository, id: ObjectId) -> Result<ObjectId, Error> {
+    let kind = repo.find_header(id)?.kind();
+    let (id, kind) = match kind {
+        gix_object::Kind::Tag => {
+            let obj = repo.find_object(id)?.peel_tags_to_end()?;
+            (obj.id, obj.kind)
+        }
+        _ => (id, kind),
+    };
+    if kind != gix_object::Kind::Commit {
+        return Err(Error::CannotTraverseNonCommit {
+            oid: id.attach(repo).shorten_or_id(),
+            actual: kind,
+        });
+    }
+    Ok(id)
+}
+
+/// Follow `num` first parents of the commit `start`, and return the commit we end up at, or the amount of ancestors
+/// along the first parent if there are fewer than `num`.
+///
+/// Commits in `graph` are traversed without decoding any object, which is much faster for large `num`.
+fn nth_ancestor(
+    repo: &crate::Repository,
+    graph: Option<&gix_commitgraph::Graph>,
+    start: ObjectId,
+    num: usize,
+) -> Result<Result<ObjectId, usize>, Error> {
+    let mut id = start;
+    let mut walked = 0;
+    loop {
+        // All ancestors of a commit in the graph are in it as well, so once there we can stay in the graph.
+        if let Some(res) = graph
+            .and_then(|graph| graph.commit_by_id(id).map(|commit| (graph, commit)))
+            .and_then(|(graph, commit)| nth_ancestor_in_graph(graph, commit, num - walked))
+        {
+            return Ok(res.map_err(|available| walked + available));
+        }
+        let commit = repo.find_object(id)?.try_into_commit().map_err(|err| {
+            let object::try_into::Error { actual, expected, id } = err;
+            Error::ObjectKind {
+                oid: id.attach(repo).shorten_or_id(),
+                actual,
+                expected,
+            }
+        })?;
+        if walked == num {
+            return Ok(Ok(id));
+        }
+        let first_parent = commit.parent_ids().next().map(crate::Id::detach);
+        match first_parent {
+            Some(parent) => {
+                id = parent;
+                walked += 1;
+            }
+            None => return Ok(Err(walked)),
+        }
+    }
+}
+
+/// Like [`nth_ancestor()`], but only uses `graph`, or returns `None` if it is corrupt.
+fn nth_ancestor_in_graph<'graph>(
+    graph: &'graph gix_commitgraph::Graph,
+    mut commit: gix_commitgraph::file::Commit<'graph>,
+    num: usize,
+) -> Option<Result<ObjectId, usize>> {
+    for walked in 0..num {
+        match commit.parent1().ok()? {
+            Some(pos) => commit = graph.commit_at(pos),
+            None => return Some(Err(walked)),
+        }
+    }
+    Some(Ok(commit.id().to_owned()))
+}
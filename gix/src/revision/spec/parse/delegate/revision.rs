@@ -10,11 +10,11 @@ use crate::{
     bstr::{BStr, BString, ByteSlice},
     ext::ReferenceExt,
     remote,
-    revision::spec::parse::{Delegate, Error, RefsHint},
+    revision::spec::parse::{AmbiguousRefKind, Delegate, Error, RefsHint, Warning},
 };
 
-impl<'repo> delegate::Revision for Delegate<'repo> {
-    fn find_ref(&mut self, name: &BStr) -> Option<()> {
+impl<'repo> Delegate<'repo, '_, '_> {
+    pub(super) fn find_ref(&mut self, name: &BStr) -> Option<()> {
         self.unset_disambiguate_call();
         if !self.err.is_empty() && self.refs[self.idx].is_some() {
             return None;
@@ -22,20 +22,57 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         match self.repo.refs.find(name) {
             Ok(r) => {
                 assert!(self.refs[self.idx].is_none(), "BUG: cannot set the same ref twice");
+                // `find()` picks the first match in the order `git` tries, but like `git` we let it be known if there were more.
+                let other_candidates = other_ref_candidates(self.repo, name, &r);
+                if !other_candidates.is_empty() {
+                    self.warnings.push(Warning::AmbiguousReferenceName {
+                        name: name.into(),
+                        candidates: std::iter::once(&r)
+                            .chain(&other_candidates)
+                            .map(|r| r.name.as_bstr().into())
+                            .collect(),
+                    });
+                    if self.opts.ref_candidates {
+                        self.ref_candidates[self.idx] = std::iter::once(r.clone()).chain(other_candidates).collect();
+                    }
+                }
+                self.record_ref(&r);
                 self.refs[self.idx] = Some(r);
                 Some(())
             }
             Err(err) => {
+                if self.opts.refs_ignore_case {
+                    match refs_ignoring_case(self.repo, name) {
+                        Ok(mut matches) if matches.len() == 1 => {
+                            let r = matches.pop().expect("one match");
+                            self.record_ref(&r);
+                            self.refs[self.idx] = Some(r);
+                            return Some(());
+                        }
+                        Ok(matches) if matches.len() > 1 => {
+                            self.err.push(Error::AmbiguousReferenceNameIgnoringCase {
+                                name: name.into(),
+                                candidates: matches.into_iter().map(|r| r.name).collect(),
+                            });
+                            return None;
+                        }
+                        Ok(_) => {}
+                        Err(err) => {
+                            self.err.push(err);
+                            return None;
+                        }
+                    }
+                }
                 self.err.push(err.into());
                 None
             }
         }
     }
 
-    fn disambiguate_prefix(
+    pub(super) fn disambiguate_prefix(
         &mut self,
         prefix: gix_hash::Prefix,
-        _must_be_commit: Option<delegate::PrefixHint<'_>>,
+        hint: Option<delegate::PrefixHint<'_>>,
     ) -> Option<()> {
         self.last_call_was_disambiguate_prefix[self.idx] = true;
         let mut candidates = Some(HashSet::default());
@@ -45,11 +82,30 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         let res = if prefix.as_oid() == empty_tree_id {
             candidates.as_mut().expect("set").insert(empty_tree_id);
             Ok(Some(Err(())))
+        } else if let Some(budget) = self.opts.disambiguation_budget {
+            let candidates = candidates.as_mut().expect("set");
+            let limit = self
+                .opts
+                .max_prefix_candidates
+                .map_or(usize::MAX, |limit| limit.saturating_add(1));
+            let deadline = std::time::Instant::now() + budget;
+            self.repo
+                .objects
+                .lookup_prefix_with_deadline(prefix, candidates, limit, || std::time::Instant::now() >= deadline)
+        } else if let Some(limit) = self.opts.max_prefix_candidates {
+            let candidates = candidates.as_mut().expect("set");
+            self.repo
+                .objects
+                .lookup_prefix_with_limit(prefix, candidates, limit.saturating_add(1))
         } else {
             self.repo.objects.lookup_prefix(prefix, candidates.as_mut())
         };
 
         match res {
+            Err(gix_odb::store::prefix::lookup::Error::DeadlineExceeded) => {
+                self.err.push(Error::DisambiguationTimedOut { prefix });
+                None
+            }
             Err(err) => {
                 self.err.push(err.into());
                 None
@@ -60,7 +116,47 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
             }
             Ok(Some(Ok(_) | Err(()))) => {
                 assert!(self.objs[self.idx].is_none(), "BUG: cannot set the same prefix twice");
-                let candidates = candidates.expect("set above");
+                let mut candidates = candidates.expect("set above");
+                if let Some(limit) = self
+                    .opts
+                    .max_prefix_candidates
+                    .filter(|limit| candidates.len() > *limit)
+                {
+                    // A reference of the same name can still be used, but there are too many objects to choose from.
+                    if matches!(
+                        self.opts.refs_hint,
+                        RefsHint::PreferRef | RefsHint::PreferObjectOnFullLengthHexShaUseRefOtherwise
+                    ) {
+                        if let Ok(ref_) = self.repo.refs.find(&prefix.to_string()) {
+                            assert!(self.refs[self.idx].is_none(), "BUG: cannot set the same ref twice");
+                            self.record_ref(&ref_);
+                            self.refs[self.idx] = Some(ref_);
+                            return Some(());
+                        }
+                    }
+                    self.err
+                        .push(Error::AmbiguousPrefixCandidatesLimitReached { prefix, limit });
+                    return None;
+                }
+                if candidates.len() > 1 && (hint.is_some() || self.kind_implies_committish()) {
+                    // All hints imply a commit, and all positions but the one of a single revision imply a committish,
+                    // so other objects can be dropped right away. If nothing remains, keep all candidates for the
+                    // fallback-disambiguation to produce the appropriate error.
+                    let commits: HashSet<_> = candidates
+                        .iter()
+                        .filter(|id| match self.repo.find_header(**id).map(|header| header.kind()) {
+                            Ok(gix_object::Kind::Commit) => true,
+                            Ok(gix_object::Kind::Tag) => {
+                                hint.is_none() && super::peel(self.repo, id, gix_object::Kind::Commit).is_ok()
+                            }
+                            _ => false,
+                        })
+                        .copied()
+                        .collect();
+                    if !commits.is_empty() {
+                        candidates = commits;
+                    }
+                }
                 match self.opts.refs_hint {
                     RefsHint::PreferObjectOnFullLengthHexShaUseRefOtherwise
                         if prefix.hex_len() == candidates.iter().next().expect("at least one").kind().len_in_hex() =>
@@ -82,11 +178,18 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
                                     self.refs[self.idx] = Some(ref_.clone());
                                     self.err.push(Error::AmbiguousRefAndObject {
                                         prefix,
+                                        kind: ambiguous_ref_kind(self.repo, &ref_),
                                         reference: ref_,
                                     });
-                                    self.err.push(Error::ambiguous(candidates, prefix, self.repo));
+                                    self.err.push(Error::ambiguous(
+                                        candidates,
+                                        prefix,
+                                        self.repo,
+                                        self.opts.ambiguity_summaries,
+                                    ));
                                     None
                                 } else {
+                                    self.record_ref(&ref_);
                                     self.refs[self.idx] = Some(ref_);
                                     Some(())
                                 }
@@ -103,67 +206,120 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         }
     }
 
-    fn reflog(&mut self, query: ReflogLookup) -> Option<()> {
+    pub(super) fn reflog(&mut self, query: ReflogLookup) -> Option<()> {
         self.unset_disambiguate_call();
+        let is_implied_by_head = self.refs[self.idx].is_none();
+        let r = match &mut self.refs[self.idx] {
+            Some(r) => r.clone().attach(self.repo),
+            val @ None => match self.repo.head().map(crate::Head::try_into_referent) {
+                Ok(Some(r)) => {
+                    *val = Some(r.clone().detach());
+                    r
+                }
+                Ok(None) => {
+                    self.err.push(Error::UnbornHeadsHaveNoRefLog);
+                    return None;
+                }
+                Err(err) => {
+                    self.err.push(err.into());
+                    return None;
+                }
+            },
+        };
+        if is_implied_by_head {
+            self.record_head();
+            self.record_ref(&r.inner);
+        }
+        let mut platform = r.log_iter();
         match query {
-            ReflogLookup::Date(_date) => {
-                // TODO: actually do this - this should be possible now despite incomplete date parsing
-                self.err.push(Error::Planned {
-                    dependency: "remote handling and ref-specs are fleshed out more",
-                });
-                None
-            }
-            ReflogLookup::Entry(no) => {
-                let r = match &mut self.refs[self.idx] {
-                    Some(r) => r.clone().attach(self.repo),
-                    val @ None => match self.repo.head().map(crate::Head::try_into_referent) {
-                        Ok(Some(r)) => {
-                            *val = Some(r.clone().detach());
-                            r
-                        }
-                        Ok(None) => {
-                            self.err.push(Error::UnbornHeadsHaveNoRefLog);
-                            return None;
-                        }
-                        Err(err) => {
-                            self.err.push(err.into());
-                            return None;
-                        }
-                    },
-                };
-                let mut platform = r.log_iter();
-                match platform.rev().ok().flatten() {
-                    Some(mut it) => match it.nth(no).and_then(Result::ok) {
-                        Some(line) => {
+            ReflogLookup::Date(date) => match platform.rev().ok().flatten() {
+                Some(it) => {
+                    // Entries are seen from newest to oldest, and the first one that was written at or before `date`
+                    // is the one that was current at the time.
+                    let mut oldest = None;
+                    for line in it.filter_map(Result::ok) {
+                        if line.signature.time.seconds <= date.seconds {
                             self.objs[self.idx]
                                 .get_or_insert_with(HashSet::default)
                                 .insert(line.new_oid);
+                            return Some(());
+                        }
+                        oldest = Some(line);
+                    }
+                    match oldest {
+                        Some(line) => {
+                            self.warnings.push(Warning::RefLogDatePrecedesOldestEntry {
+                                reference: r.name().as_bstr().into(),
+                                desired: date,
+                                oldest: line.signature.time,
+                            });
+                            // Like git, use the value the reference had before the oldest entry, if there was one.
+                            let id = if line.previous_oid.is_null() {
+                                line.new_oid
+                            } else {
+                                line.previous_oid
+                            };
+                            self.objs[self.idx].get_or_insert_with(HashSet::default).insert(id);
                             Some(())
                         }
                         None => {
-                            let available = platform.rev().ok().flatten().map_or(0, Iterator::count);
-                            self.err.push(Error::RefLogEntryOutOfRange {
-                                reference: r.detach(),
-                                desired: no,
-                                available,
+                            self.err.push(Error::EmptyRefLog {
+                                reference: r.name().as_bstr().into(),
+                                action: "lookup entry by date",
                             });
                             None
                         }
-                    },
+                    }
+                }
+                None => {
+                    self.err.push(Error::MissingRefLog {
+                        reference: r.name().as_bstr().into(),
+                        action: "lookup entry by date",
+                    });
+                    None
+                }
+            },
+            // Entry 0 is the `new` value of the most recent entry, which usually but not necessarily is the current value
+            // of the reference, for instance if it was changed without logging.
+            ReflogLookup::Entry(no) => match platform.rev().ok().flatten() {
+                Some(mut it) => match it.nth(no).and_then(Result::ok) {
+                    Some(line) => {
+                        self.objs[self.idx]
+                            .get_or_insert_with(HashSet::default)
+                            .insert(line.new_oid);
+                        Some(())
+                    }
                     None => {
-                        self.err.push(Error::MissingRefLog {
-                            reference: r.name().as_bstr().into(),
-                            action: "lookup entry",
+                        let available = platform.rev().ok().flatten().map_or(0, Iterator::count);
+                        self.err.push(if available == 0 {
+                            Error::EmptyRefLog {
+                                reference: r.name().as_bstr().into(),
+                                action: "lookup entry",
+                            }
+                        } else {
+                            Error::RefLogEntryOutOfRange {
+                                reference: r.detach(),
+                                desired: no,
+                                available,
+                            }
                         });
                         None
                     }
+                },
+                None => {
+                    self.err.push(Error::MissingRefLog {
+                        reference: r.name().as_bstr().into(),
+                        action: "lookup entry",
+                    });
+                    None
                 }
-            }
+            },
         }
     }
 
-    fn nth_checked_out_branch(&mut self, branch_no: usize) -> Option<()> {
+    pub(super) fn nth_checked_out_branch(&mut self, branch_no: usize) -> Option<()> {
         self.unset_disambiguate_call();
+        debug_assert_ne!(branch_no, 0, "BUG: the parser rejects `@{{-0}}`");
         fn prior_checkouts_iter<'a>(
             platform: &'a mut gix_ref::file::log::iter::Platform<'static, '_>,
         ) -> Result<impl Iterator<Item = (BString, ObjectId)> + 'a, Error> {
@@ -188,11 +344,13 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
                 return None;
             }
         };
-        match prior_checkouts_iter(&mut head.log_iter()).map(|mut it| it.nth(branch_no.saturating_sub(1))) {
+        self.record_head();
+        match prior_checkouts_iter(&mut head.log_iter()).map(|mut it| it.nth(branch_no - 1)) {
             Ok(Some((ref_name, id))) => {
                 let id = match self.repo.find_reference(ref_name.as_bstr()) {
                     Ok(mut r) => {
                         let id = r.peel_to_id_in_place().map(crate::Id::detach).unwrap_or(id);
+                        self.record_ref(&r.inner);
                         self.refs[self.idx] = Some(r.detach());
                         id
                     }
@@ -217,8 +375,9 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         }
     }
 
-    fn sibling_branch(&mut self, kind: SiblingBranch) -> Option<()> {
+    pub(super) fn sibling_branch(&mut self, kind: SiblingBranch) -> Option<()> {
         self.unset_disambiguate_call();
+        let is_implied_by_head = self.refs[self.idx].is_none();
         let reference = match &mut self.refs[self.idx] {
             val @ None => match self.repo.head().map(crate::Head::try_into_referent) {
                 Ok(Some(r)) => {
@@ -236,14 +395,23 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
             },
             Some(r) => r.clone().attach(self.repo),
         };
+        if is_implied_by_head {
+            self.record_head();
+            self.record_ref(&reference.inner);
+        }
         let direction = match kind {
             SiblingBranch::Upstream => remote::Direction::Fetch,
             SiblingBranch::Push => remote::Direction::Push,
         };
         match reference.remote_tracking_ref_name(direction) {
-            None => self.err.push(Error::NoTrackingBranch {
-                name: reference.inner.name,
-                direction,
+            None => self.err.push(match direction {
+                remote::Direction::Fetch => Error::NoTrackingBranch {
+                    name: reference.inner.name,
+                    direction,
+                },
+                remote::Direction::Push => Error::NoPushDestination {
+                    branch: reference.inner.name,
+                },
             }),
             Some(Err(err)) => self.err.push(Error::GetTrackingBranch {
                 name: reference.inner.name,
@@ -257,6 +425,7 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
                     source: Box::new(err),
                 }),
                 Ok(r) => {
+                    self.record_ref(&r.inner);
                     self.refs[self.idx] = r.inner.into();
                     return Some(());
                 }
@@ -265,3 +434,58 @@ impl<'repo> delegate::Revision for Delegate<'repo> {
         None
     }
 }
+
+/// Return all full names that `name` could be expanded to, in the order in which `git` tries them.
+fn expanded_names(name: &BStr) -> impl Iterator<Item = String> + '_ {
+    ["", "refs/", "refs/tags/", "refs/heads/", "refs/remotes/"]
+        .iter()
+        .map(move |prefix| format!("{prefix}{name}"))
+        .chain(Some(format!("refs/remotes/{name}/HEAD")))
+}
+
+/// Return all references that `name` could refer to besides `found`, in the order in which `git` tries them.
+/// As `found` is the first match, only the expansions after the one matching `found` need to be tried.
+fn other_ref_candidates(repo: &crate::Repository, name: &BStr, found: &gix_ref::Reference) -> Vec<gix_ref::Reference> {
+    expanded_names(name)
+        .skip_while(|full_name| found.name.as_bstr() != full_name.as_str())
+        .skip(1)
+        .filter_map(|full_name| {
+            repo.refs
+                .try_find(full_name.as_str())
+                .ok()
+                .flatten()
+                .filter(|r| r.name.as_bstr() == full_name.as_str())
+        })
+        .collect()
+}
+
+/// Return all references whose name equals the first expansion of `name` that any reference matches, ignoring case.
+fn refs_ignoring_case(repo: &crate::Repository, name: &BStr) -> Result<Vec<gix_ref::Reference>, Error> {
+    let full_names: Vec<_> = expanded_names(name).collect();
+    let mut matches_by_expansion = vec![Vec::new(); full_names.len()];
+    for r in repo.references()?.all()?.filter_map(Result::ok) {
+        if let Some(pos) = full_names
+            .iter()
+            .position(|full_name| r.name().as_bstr().eq_ignore_ascii_case(full_name.as_bytes()))
+        {
+            matches_by_expansion[pos].push(r.detach());
+        }
+    }
+    Ok(matches_by_expansion
+        .into_iter()
+        .find(|matches| !matches.is_empty())
+        .unwrap_or_default())
+}
+
+/// Classify `reference` so users can tell it apart from objects of the same name.
+fn ambiguous_ref_kind(repo: &crate::Repository, reference: &gix_ref::Reference) -> AmbiguousRefKind {
+    match reference.name.category() {
+        Some(gix_ref::Category::LocalBranch) => AmbiguousRefKind::Branch,
+        Some(gix_ref::Category::RemoteBranch) => AmbiguousRefKind::RemoteBranch,
+        Some(gix_ref::Category::Tag) => match reference.target.try_id().map(|id| repo.find_header(id)) {
+            Some(Ok(header)) if header.kind() == gix_object::Kind::Tag => AmbiguousRefKind::AnnotatedTag,
+            _ => AmbiguousRefKind::LightweightTag,
+        },
+        _ => AmbiguousRefKind::Other,
+    }
+}
@@ -18,6 +18,16 @@ pub enum CandidateInfo {
         /// The kind of the object.
         kind: gix_object::Kind,
     },
+    /// The candidate is a tree, as described if [`Options::ambiguity_summaries`][super::Options::ambiguity_summaries] is set.
+    Tree {
+        /// The amount of entries in the tree.
+        num_entries: usize,
+    },
+    /// The candidate is a blob, as described if [`Options::ambiguity_summaries`][super::Options::ambiguity_summaries] is set.
+    Blob {
+        /// The size of the blob in bytes.
+        size: usize,
+    },
     /// The candidate is a tag.
     Tag {
         /// The name of the tag.
@@ -38,6 +48,12 @@ impl std::fmt::Display for CandidateInfo {
             CandidateInfo::FindError { source } => write!(f, "lookup error: {source}"),
             CandidateInfo::Tag { name } => write!(f, "tag {name:?}"),
             CandidateInfo::Object { kind } => std::fmt::Display::fmt(kind, f),
+            CandidateInfo::Tree { num_entries } => write!(
+                f,
+                "tree with {num_entries} {}",
+                if *num_entries == 1 { "entry" } else { "entries" }
+            ),
+            CandidateInfo::Blob { size } => write!(f, "blob of {size} {}", if *size == 1 { "byte" } else { "bytes" }),
             CandidateInfo::Commit { date, title } => {
                 write!(f, "commit {} {title:?}", date.format(gix_date::time::format::SHORT))
             }
@@ -46,7 +62,13 @@ impl std::fmt::Display for CandidateInfo {
 }
 
 impl Error {
-    pub(crate) fn ambiguous(candidates: HashSet<ObjectId>, prefix: gix_hash::Prefix, repo: &Repository) -> Self {
+    /// Describe the `candidates` of the ambiguous `prefix`, and if `summarize` is `true`, add details to trees and blobs.
+    pub(crate) fn ambiguous(
+        candidates: HashSet<ObjectId>,
+        prefix: gix_hash::Prefix,
+        repo: &Repository,
+        summarize: bool,
+    ) -> Self {
         #[derive(PartialOrd, Ord, Eq, PartialEq, Copy, Clone)]
         enum Order {
             Tag,
@@ -82,6 +104,13 @@ impl Error {
                 .map(|(oid, find_result, _)| {
                     let info = match find_result {
                         Ok(obj) => match obj.kind {
+                            gix_object::Kind::Tree if summarize => match gix_object::TreeRef::from_bytes(&obj.data) {
+                                Ok(tree) => CandidateInfo::Tree {
+                                    num_entries: tree.entries.len(),
+                                },
+                                Err(_) => CandidateInfo::Object { kind: obj.kind },
+                            },
+                            gix_object::Kind::Blob if summarize => CandidateInfo::Blob { size: obj.data.len() },
                             gix_object::Kind::Tree | gix_object::Kind::Blob => CandidateInfo::Object { kind: obj.kind },
                             gix_object::Kind::Tag => {
                                 let tag = obj.to_tag_ref();
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use gix_hash::ObjectId;
 use gix_macros::momo;
@@ -8,7 +8,9 @@ use crate::{bstr::BStr, revision::Spec, Repository};
 
 mod types;
 use crate::bstr::BString;
-pub use types::{Error, ObjectKindHint, Options, RefsHint};
+pub use types::{
+    AmbiguousRefKind, Error, FetchMissingObject, ObjectKindHint, Options, RefCompletion, RefsHint, Warning,
+};
 
 ///
 #[allow(clippy::empty_docs)]
@@ -33,20 +35,95 @@ pub mod error;
 impl<'repo> Spec<'repo> {
     /// Parse `spec` and use information from `repo` to resolve it, using `opts` to learn how to deal with ambiguity.
     ///
-    /// Note that it's easier and to use [`repo.rev_parse()`][Repository::rev_parse()] instead.
+    /// This is the single entry point for resolving a revspec string, as it drives the parser with a delegate that
+    /// performs all lookups and turns its outcome into a `Spec`.
+    /// If resolution fails, all errors encountered along the way are returned, most specific first, possibly
+    /// combined into [`Error::Multi`], and with failures in ranges attributed to the endpoint they occurred in.
+    ///
+    /// Note that it's easier to use [`repo.rev_parse()`][Repository::rev_parse()] instead, which configures `opts`
+    /// from the repository configuration.
     #[momo]
     pub fn from_bstr<'a>(spec: impl Into<&'a BStr>, repo: &'repo Repository, opts: Options) -> Result<Self, Error> {
+        Self::from_bstr_inner(spec.into(), repo, None, None, opts)
+    }
+
+    /// Like [`from_bstr()`][Self::from_bstr()], but resolve index lookups like `:path` or `:1:path` against `index`
+    /// instead of the index of `repo`.
+    ///
+    /// This is useful for tools that operate on an alternate index, like one pointed to by `GIT_INDEX_FILE` or a temporary one
+    /// while an operation is in progress. Such lookups then also work in bare repositories.
+    #[momo]
+    pub fn from_bstr_with_index<'a>(
+        spec: impl Into<&'a BStr>,
+        repo: &'repo Repository,
+        index: &gix_index::State,
+        opts: Options,
+    ) -> Result<Self, Error> {
+        Self::from_bstr_inner(spec.into(), repo, Some(index), None, opts)
+    }
+
+    /// Like [`from_bstr()`][Self::from_bstr()], but if [`Options::fetch_missing_objects`] is set, call `fetch` with the id of
+    /// each object that can't be found while peeling, and try again once it returns successfully.
+    ///
+    /// This allows to resolve specs like `HEAD:file` in partial clones, whose missing trees and blobs can be fetched on demand.
+    /// Each object is only fetched once, and if `fetch` fails, resolution fails with
+    /// [`FetchMissingObject`][Error::FetchMissingObject].
+    #[momo]
+    pub fn from_bstr_with_fetch<'a>(
+        spec: impl Into<&'a BStr>,
+        repo: &'repo Repository,
+        fetch: &mut FetchMissingObject<'_>,
+        opts: Options,
+    ) -> Result<Self, Error> {
+        Self::from_bstr_inner(spec.into(), repo, None, Some(fetch), opts)
+    }
+
+    fn from_bstr_inner<'fetch>(
+        spec: &BStr,
+        repo: &'repo Repository,
+        index: Option<&gix_index::State>,
+        fetch: Option<&'fetch mut FetchMissingObject<'fetch>>,
+        opts: Options,
+    ) -> Result<Self, Error> {
         let mut delegate = Delegate::new(repo, opts);
-        match gix_revision::spec::parse(spec.into(), &mut delegate) {
-            Err(parse::Error::Delegate) => Err(delegate.into_err()),
+        delegate.index = index;
+        delegate.fetch = fetch;
+        let res = gix_revision::spec::parse(spec, &mut delegate);
+        if delegate.left_endpoint_failed {
+            let is_range = delegate.kind_is_range();
+            let err = delegate.into_err();
+            return Err(if is_range {
+                Error::LeftEndpoint { source: err.into() }
+            } else {
+                err
+            });
+        }
+        match res {
+            Err(parse::Error::Delegate) => {
+                let failed_on_right_endpoint = delegate.idx == 1;
+                let err = delegate.into_err();
+                Err(if failed_on_right_endpoint {
+                    Error::RightEndpoint { source: err.into() }
+                } else {
+                    err
+                })
+            }
             Err(err) => Err(err.into()),
             Ok(()) => delegate.into_rev_spec(),
         }
     }
 }
 
-struct Delegate<'repo> {
+/// The delegate to [`gix_revision::spec::parse()`] which resolves revisions in a repository, as used by [`Spec::from_bstr()`].
+///
+/// Use it to drive the parser directly, for instance to look at the [errors](Self::errors()) accumulated so far
+/// and stop early, before obtaining the final [`Spec`](Self::into_rev_spec()) or [error](Self::into_err()).
+pub struct Delegate<'repo, 'index, 'fetch> {
     refs: [Option<gix_ref::Reference>; 2],
+    /// All references that a reference name could have referred to, if there was more than one.
+    ref_candidates: [Vec<gix_ref::Reference>; 2],
+    /// All references consulted during resolution, in order, if [`Options::ref_chain`] is set.
+    ref_chain: Vec<gix_ref::Reference>,
     objs: [Option<HashSet<ObjectId>>; 2],
     /// Path specified like `@:<path>` or `:<path>` for later use when looking up specs.
     /// Note that it terminates spec parsing, so it's either `0` or `1`, never both.
@@ -55,13 +132,24 @@ struct Delegate<'repo> {
     ambiguous_objects: [Option<HashSet<ObjectId>>; 2],
     idx: usize,
     kind: Option<gix_revision::spec::Kind>,
+    /// If true, resolving the left endpoint failed, and we keep parsing only to learn if it's part of a range.
+    left_endpoint_failed: bool,
+    /// A mapping of trees to the commits they were obtained from, for use when navigating trees.
+    /// Only filled in if [`Options::allow_tree_navigation`] is set.
+    tree_origins: [HashMap<ObjectId, ObjectId>; 2],
 
     opts: Options,
     err: Vec<Error>,
+    /// Non-fatal issues to pass on to the caller.
+    warnings: Vec<Warning>,
     /// The ambiguous prefix obtained during a call to `disambiguate_prefix()`.
     prefix: [Option<gix_hash::Prefix>; 2],
     /// If true, we didn't try to do any other transformation which might have helped with disambiguation.
     last_call_was_disambiguate_prefix: [bool; 2],
+    /// The index to use for lookups like `:path` instead of the one of `repo`.
+    index: Option<&'index gix_index::State>,
+    /// The function to obtain objects missing while peeling, if [`Options::fetch_missing_objects`] is set.
+    fetch: Option<&'fetch mut FetchMissingObject<'fetch>>,
 
     repo: &'repo Repository,
 }
@@ -21,6 +21,45 @@ pub enum RefsHint {
     Fail,
 }
 
+/// The kind of reference that matched a short hash along with objects, as reported by [`Error::AmbiguousRefAndObject`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AmbiguousRefKind {
+    /// A branch in `refs/heads/`.
+    Branch,
+    /// A remote tracking branch in `refs/remotes/`.
+    RemoteBranch,
+    /// A tag in `refs/tags/` which doesn't point to a tag object.
+    LightweightTag,
+    /// A tag in `refs/tags/` which points to a tag object.
+    AnnotatedTag,
+    /// Any other reference.
+    Other,
+}
+
+impl std::fmt::Display for AmbiguousRefKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AmbiguousRefKind::Branch => "branch",
+            AmbiguousRefKind::RemoteBranch => "remote branch",
+            AmbiguousRefKind::LightweightTag => "lightweight tag",
+            AmbiguousRefKind::AnnotatedTag => "annotated tag",
+            AmbiguousRefKind::Other => "reference",
+        })
+    }
+}
+
+/// A reference that a partially typed name can be completed to, as returned by
+/// [`Repository::rev_parse_ref_completions()`][crate::Repository::rev_parse_ref_completions()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefCompletion {
+    /// The full name of the reference, like `refs/heads/main`.
+    pub name: gix_ref::FullName,
+    /// Whether the reference is a branch, a remote branch or a tag.
+    pub category: gix_ref::Category<'static>,
+    /// The name to complete to, in the form that was started, so `main` for `ma`, but `refs/heads/main` for `refs/heads/m`.
+    pub completion: BString,
+}
+
 /// A hint to know which object kind to prefer if multiple objects match a prefix.
 ///
 /// This disambiguation mechanism is applied only if there is no disambiguation hints in the spec itself.
@@ -49,6 +88,110 @@ pub struct Options {
     /// If `None`, the rev-spec itself must disambiguate the object by drilling down to desired kinds or applying
     /// other disambiguating transformations.
     pub object_kind_hint: Option<ObjectKindHint>,
+    /// If `true` and the spec resolves to a single blob, obtain its size from the object header and make it available
+    /// via [`blob_size()`][crate::revision::Spec::blob_size()].
+    ///
+    /// This is cheaper than decoding the blob as only the header has to be read.
+    pub blob_size: bool,
+    /// If `true`, when a reference name like `origin` expands to more than one existing reference, for instance both
+    /// `refs/heads/origin` and `refs/remotes/origin/HEAD`, all of them are collected and made available via
+    /// [`ref_candidates()`][crate::revision::Spec::ref_candidates()].
+    ///
+    /// Resolution proceeds with the reference that `git` would pick, so this merely allows interactive tools to let the
+    /// user choose another one.
+    pub ref_candidates: bool,
+    /// If `true`, collect all references that were consulted while resolving the spec, in order, and make them available via
+    /// [`ref_chain()`][crate::revision::Spec::ref_chain()].
+    ///
+    /// This allows tools to show how a spec was resolved, for instance that `@{-1}` consulted the reflog of `HEAD` to find
+    /// `refs/heads/feature`, which then points to a commit.
+    pub ref_chain: bool,
+    /// If `true`, reference names that can't be found as given are looked up again while ignoring their case,
+    /// so `MAIN` may resolve to `refs/heads/main`.
+    ///
+    /// Names are expanded just like without this option, and if more than one reference differs from an expanded name
+    /// only by case, like `refs/heads/Main` and `refs/heads/main` for `MAIN`, the lookup fails with
+    /// [`AmbiguousReferenceNameIgnoringCase`][Error::AmbiguousReferenceNameIgnoringCase].
+    /// Note that this iterates all references of the repository each time a name can't be found.
+    pub refs_ignore_case: bool,
+    /// If `true`, allow `~` and `^` to navigate trees as long as they were obtained from a commit, like in `@^{tree}~1`.
+    /// The navigation then happens on the commit the tree was obtained from, and yields the tree of the resulting commit,
+    /// making `@^{tree}~1` the same as `@~1^{tree}`.
+    ///
+    /// Trees that weren't obtained from a commit still can't be navigated. This is non-standard, hence it's off by default.
+    pub allow_tree_navigation: bool,
+    /// If `true`, [ambiguity errors][Error::AmbiguousPrefix] describe tree candidates by their amount of entries and blob
+    /// candidates by their size, instead of only naming their kind.
+    ///
+    /// This costs decoding all candidate trees, which is wasted if the error is discarded, hence it's off by default.
+    pub ambiguity_summaries: bool,
+    /// If `Some(limit)`, stop looking up objects matching a short id once more than `limit` of them were found and fail
+    /// with [`AmbiguousPrefixCandidatesLimitReached`][Error::AmbiguousPrefixCandidatesLimitReached], instead of
+    /// collecting all of them for disambiguation.
+    ///
+    /// This bounds the time and memory spent on very short prefixes, which may match a large portion of all objects.
+    /// Note that this also prevents disambiguating such prefixes by object kind.
+    pub max_prefix_candidates: Option<usize>,
+    /// If `Some(budget)`, give up looking up objects matching a short id once `budget` has passed and fail with
+    /// [`DisambiguationTimedOut`][Error::DisambiguationTimedOut].
+    ///
+    /// This keeps interactive tools responsive on object databases that are slow to search, like those with many loose objects.
+    /// The budget is checked while searching, so the lookup may take a little longer than that, and it applies to each
+    /// short id separately.
+    pub disambiguation_budget: Option<std::time::Duration>,
+    /// If `true`, objects that can't be found while peeling, like the trees and blobs missing in partial clones, are passed to
+    /// the function provided to [`Spec::from_bstr_with_fetch()`][crate::revision::Spec::from_bstr_with_fetch()] to obtain
+    /// them, before looking them up once more.
+    ///
+    /// It has no effect if no such function is provided, and it's off by default as fetching can be costly.
+    pub fetch_missing_objects: bool,
+}
+
+/// A function to make the object with the given id available in the object database of the repository, for instance by fetching
+/// it from the remote of a partial clone, for use with [`Spec::from_bstr_with_fetch()`][crate::revision::Spec::from_bstr_with_fetch()].
+pub type FetchMissingObject<'a> =
+    dyn FnMut(&gix_hash::oid) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> + 'a;
+
+/// A non-fatal issue encountered while resolving a rev-spec, made available via
+/// [`Spec::warnings()`][crate::revision::Spec::warnings()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// A reflog lookup by date like `main@{1979-02-26}` asked for a date before the oldest reflog entry was written,
+    /// so the oldest known value of the reference was used instead.
+    RefLogDatePrecedesOldestEntry {
+        /// The full name of the reference whose reflog was searched.
+        reference: BString,
+        /// The date at which the value of the reference was requested.
+        desired: gix_date::Time,
+        /// The date of the oldest reflog entry.
+        oldest: gix_date::Time,
+    },
+    /// A reference name like `origin` matched more than one reference when trying `<name>`, `refs/<name>`,
+    /// `refs/tags/<name>`, `refs/heads/<name>`, `refs/remotes/<name>` and `refs/remotes/<name>/HEAD` in that order,
+    /// so the first of them was used, just like `git` would.
+    AmbiguousReferenceName {
+        /// The name as given in the spec.
+        name: BString,
+        /// The full names of all matching references, in order of precedence, with the first one being the one that was used.
+        candidates: Vec<BString>,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::RefLogDatePrecedesOldestEntry { reference, oldest, .. } => write!(
+                f,
+                "Log for {reference:?} only goes back to {}",
+                oldest.format(gix_date::time::format::DEFAULT)
+            ),
+            Warning::AmbiguousReferenceName { name, candidates } => write!(
+                f,
+                "Reference name {name:?} is ambiguous and was resolved to {:?}",
+                candidates.first().expect("at least two candidates")
+            ),
+        }
+    }
 }
 
 /// The error returned by [`crate::Repository::rev_parse()`].
@@ -66,6 +209,8 @@ pub enum Error {
         name: gix_ref::FullName,
         direction: remote::Direction,
     },
+    #[error("Branch named {branch} does not have a push destination configured")]
+    NoPushDestination { branch: gix_ref::FullName },
     #[error("Error when obtaining {} tracking branch for {name}", direction.as_str())]
     GetTrackingBranch {
         name: gix_ref::FullName,
@@ -76,6 +221,8 @@ pub enum Error {
     Planned { dependency: &'static str },
     #[error("Reference {reference:?} does not have a reference log, cannot {action}")]
     MissingRefLog { reference: BString, action: &'static str },
+    #[error("Reference {reference:?} has a reference log without entries, cannot {action}")]
+    EmptyRefLog { reference: BString, action: &'static str },
     #[error("HEAD has {available} prior checkouts and checkout number {desired} is out of range")]
     PriorCheckoutOutOfRange { desired: usize, available: usize },
     #[error("Reference {:?} has {available} ref-log entries and entry number {desired} is out of range", reference.name.as_bstr())]
@@ -105,6 +252,10 @@ pub enum Error {
         stage_hint: Option<gix_index::entry::Stage>,
         exists: bool,
     },
+    #[error("Path {desired_path:?} cannot be looked up in the index as the repository is bare and has no index file")]
+    NoWorktree { desired_path: BString },
+    #[error("Path {desired_path:?} cannot be looked up in the index as there is no index file yet")]
+    NoIndex { desired_path: BString },
     #[error(transparent)]
     FindHead(#[from] reference::find::existing::Error),
     #[error(transparent)]
@@ -139,12 +290,21 @@ pub enum Error {
     )]
     NoRegexMatchAllRefs { regex: BString, commits_searched: usize },
     #[error(
-    "The short hash {prefix} matched both the reference {} and at least one object", reference.name)]
+    "The short hash {prefix} matched both the {kind} {} and at least one object", reference.name)]
     AmbiguousRefAndObject {
         /// The prefix to look for.
         prefix: gix_hash::Prefix,
         /// The reference matching the prefix.
         reference: gix_ref::Reference,
+        /// The kind of `reference`, to help telling it apart from the objects.
+        kind: AmbiguousRefKind,
+    },
+    #[error("Reference name {name:?} is ambiguous when ignoring case, candidates are: {}", candidates.iter().map(|name| name.as_bstr().to_string()).collect::<Vec<_>>().join(", "))]
+    AmbiguousReferenceNameIgnoringCase {
+        /// The name as given in the spec.
+        name: BString,
+        /// The full names of all references that only differ by case.
+        candidates: Vec<gix_ref::FullName>,
     },
     #[error(transparent)]
     IdFromHex(#[from] gix_hash::decode::Error),
@@ -156,12 +316,22 @@ pub enum Error {
     LookupPrefix(#[from] gix_odb::store::prefix::lookup::Error),
     #[error(transparent)]
     PeelToKind(#[from] object::peel::to_kind::Error),
+    #[error("Could not fetch missing object {oid}")]
+    FetchMissingObject {
+        oid: gix_hash::ObjectId,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
     #[error("Object {oid} was a {actual}, but needed it to be a {expected}")]
     ObjectKind {
         oid: gix_hash::Prefix,
         actual: gix_object::Kind,
         expected: gix_object::Kind,
     },
+    #[error("Cannot traverse the ancestry of {actual} {oid} as it isn't a commit or a tag pointing to one")]
+    CannotTraverseNonCommit {
+        oid: gix_hash::Prefix,
+        actual: gix_object::Kind,
+    },
     #[error(transparent)]
     Parse(#[from] gix_revision::spec::parse::Error),
     #[error("An object prefixed {prefix} could not be found")]
@@ -171,12 +341,40 @@ pub enum Error {
         prefix: gix_hash::Prefix,
         info: Vec<(gix_hash::Prefix, super::error::CandidateInfo)>,
     },
+    #[error("Looking up objects for short id {prefix} took longer than allowed")]
+    DisambiguationTimedOut { prefix: gix_hash::Prefix },
+    #[error("Short id {prefix} is ambiguous (more than {limit} candidates)")]
+    AmbiguousPrefixCandidatesLimitReached { prefix: gix_hash::Prefix, limit: usize },
     #[error("Could not find path {path:?} in tree {tree} of parent object {object}")]
     PathNotFound {
         object: gix_hash::Prefix,
         tree: gix_hash::Prefix,
         path: BString,
     },
+    #[error("Path component {component:?} of path {path:?} is not a tree in tree {tree} of parent object {object}")]
+    PathComponentNotATree {
+        object: gix_hash::Prefix,
+        tree: gix_hash::Prefix,
+        path: BString,
+        /// The leading portion of `path` up to and including the component that isn't a tree.
+        component: BString,
+    },
+    #[error(
+        "Path {path:?} leads outside of the tree as it has more '..' components than there are directories to leave"
+    )]
+    PathOutsideOfTree { path: BString },
+    #[error(transparent)]
+    Prefix(#[from] gix_path::realpath::Error),
+    #[error("The left endpoint of the range could not be resolved")]
+    LeftEndpoint {
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("The right endpoint of the range could not be resolved")]
+    RightEndpoint {
+        #[source]
+        source: Box<Error>,
+    },
     #[error("{current}")]
     Multi {
         current: Box<dyn std::error::Error + Send + Sync + 'static>,
@@ -189,4 +387,8 @@ pub enum Error {
     Walk(#[from] crate::revision::walk::Error),
     #[error("Spec does not contain a single object id")]
     SingleNotFound,
+    #[error("Spec {spec} is a range, but a single object was expected")]
+    RangeNotAllowed { spec: gix_revision::Spec },
+    #[error("Spec {spec} doesn't name a reference, but only an object")]
+    NotAReference { spec: gix_revision::Spec },
 }
@@ -13,6 +13,8 @@ pub enum Error {
     ShallowCommits(#[from] crate::shallow::open::Error),
     #[error(transparent)]
     ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    LookupCommitInGraph(#[from] gix_revwalk::graph::try_lookup_or_insert_default::Error),
 }
 
 /// Information about a commit that we obtained naturally as part of the iteration.
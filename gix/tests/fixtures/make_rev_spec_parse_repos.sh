@@ -137,6 +137,7 @@ EOF
   baseline "0000000000e4f^{commit}"  # and with commit assertion
   baseline "0000000000^{commit}"     # ambiguous name, but there is only one commit, but git can't do it
   baseline "0000000000^0"            # another transformation that only works for commits
+  baseline "x-g0000000000"           # describe output can only refer to commits, which disambiguates
   baseline "0000000000f8f"           # disambiguated tag
   baseline "0000000000^{tag}"        # disambiguated by type, but git can't do it
 
@@ -258,6 +259,13 @@ git clone ambiguous_blob_tree_commit ambiguous_refs
   	baseline "$REF" # there is a ref and an object with the same name
 )
 
+git clone ambiguous_blob_tree_commit ambiguous_tags
+(
+    cd ambiguous_tags
+    git tag $(git rev-parse --short=12 HEAD) HEAD
+    git tag -m "annotated" $(git rev-parse --short=13 HEAD) HEAD
+)
+
 for name in committish treeish tree commit blob; do
   clone_dir=ambiguous_objects_disambiguation_config_$name
   git clone ambiguous_commits $clone_dir
@@ -389,13 +397,21 @@ EOF
   baseline "@{-6}"
 
   baseline "@{0}"
+  baseline "@{2}"
   baseline "@{3}"
+  baseline "main@{2}"
+  baseline "HEAD@{2}"
   baseline "HEAD@{5}"
   baseline "main@{12345}"
 
   baseline "@^{}"
   baseline "main^{}"
   baseline "b-tag^{}"
+  baseline "b-tag^{tag}"
+  baseline "b-tag^{commit}"
+  baseline "b-tag~1"
+  baseline "b-tag^"
+  baseline "main^{tag}"
 
   baseline "@^{tree}"
   baseline "@:"
@@ -417,3 +433,162 @@ git init new
 (cd new
   baseline '@{1}'
 )
+
+git init reflog_dates
+(cd reflog_dates
+  git commit --allow-empty -q -m first && first=$(git rev-parse @)
+  git commit --allow-empty -q -m second && second=$(git rev-parse @)
+  git commit --allow-empty -q -m third && third=$(git rev-parse @)
+  git branch truncated
+
+  null=0000000000000000000000000000000000000000
+  sig="committer <committer@example.com>"
+  # handcrafted reflogs with known timestamps, the one of `truncated` doesn't go back to the creation of the branch.
+  {
+    printf "%s %s %s 1000000000 +0000\tcommit (initial): first\n" $null $first "$sig"
+    printf "%s %s %s 1100000000 +0000\tcommit: second\n" $first $second "$sig"
+    printf "%s %s %s 1200000000 +0000\tcommit: third\n" $second $third "$sig"
+  } > .git/logs/refs/heads/main
+  {
+    printf "%s %s %s 1100000000 +0000\tcommit: second\n" $first $second "$sig"
+    printf "%s %s %s 1200000000 +0000\tcommit: third\n" $second $third "$sig"
+  } > .git/logs/refs/heads/truncated
+
+  baseline "main@{2001-09-09 01:46:40 +0000}"
+  baseline "main@{2005-01-01 00:00:00 +0000}"
+  baseline "main@{2020-01-01 00:00:00 +0000}"
+  baseline "main@{1990-01-01 00:00:00 +0000}"
+  baseline "truncated@{1990-01-01 00:00:00 +0000}"
+)
+
+git init reflog_after_reset
+(cd reflog_after_reset
+  git commit --allow-empty -q -m first
+  git commit --allow-empty -q -m second
+  git reset -q --hard @~1
+  git branch empty-log
+  : >.git/logs/refs/heads/empty-log
+  git branch no-log
+  rm .git/logs/refs/heads/no-log
+  # the reference moves without its reflog knowing
+  git branch unlogged
+  git rev-parse main@{1} >.git/refs/heads/unlogged
+
+  baseline "@{0}"
+  baseline "HEAD@{0}"
+  baseline "main@{0}"
+  baseline "main@{1}"
+  baseline "unlogged@{0}"
+  baseline "no-log@{0}"
+)
+
+# fetch from `origin`, but push to `fork`
+git init triangular
+(cd triangular
+  git commit --allow-empty -q -m first
+  git checkout -b feature
+  git commit --allow-empty -q -m feature
+  git remote add origin .
+  git remote add fork .
+  git config remote.pushDefault fork
+  git config branch.feature.remote origin
+  git config branch.feature.merge refs/heads/main
+  git fetch -q origin
+  git fetch -q fork
+)
+
+for push_default in current upstream nothing; do
+  cp -R triangular triangular-push-$push_default
+  (cd triangular-push-$push_default
+    git config push.default $push_default
+    baseline "feature@{upstream}"
+    baseline "feature@{push}"
+  )
+done
+
+
+git init nested_tree
+(cd nested_tree
+  mkdir -p src/sub
+  echo lib >src/lib.rs
+  echo mod >src/sub/mod.rs
+  git add . && git commit -q -m "nested"
+
+  baseline "@:"
+  baseline "@:src"
+  baseline "@:src/"
+  baseline "@:src/sub/"
+  baseline "@:src/lib.rs"
+  baseline "@:src/lib.rs/"
+)
+
+git init linear_with_partial_commit_graph
+(cd linear_with_partial_commit_graph
+  for n in 1 2 3 4 5; do git commit --allow-empty -q -m "in graph $n"; done
+  git commit-graph write --no-progress --reachable
+  for n in 1 2; do git commit --allow-empty -q -m "not in graph $n"; done
+
+  baseline "@~1"
+  baseline "@~2"
+  baseline "@~6"
+  baseline "@~7"
+)
+
+git init replaced
+(cd replaced
+  git commit --allow-empty -q -m "first"
+  git commit --allow-empty -q -m "original"
+  original=$(git rev-parse @)
+  git checkout -q -b replacement @~1
+  echo content >file && git add file
+  git commit -q -m "replacement"
+  git checkout -q main
+  git replace $original replacement
+
+  baseline "@^{object}"
+  baseline "@^{commit}"
+  baseline "@^{tree}"
+  baseline "@:file"
+  baseline "@^{/replacement}"
+)
+
+git init criss_cross
+(cd criss_cross
+  git commit --allow-empty -q -m "base"
+  git branch other
+  git commit --allow-empty -q -m "A1"
+  git checkout -q other
+  git commit --allow-empty -q -m "B1"
+  git checkout -q main
+  git merge -q --no-ff -m "A2" other
+  git checkout -q other
+  git merge -q --no-ff -m "B2" main~1
+  git checkout -q main
+
+  git merge-base --all main other >merge-bases.baseline
+  for spec in "main^!" "other^!" "other~2^!"; do
+    echo "$spec" >>commit-only.baseline
+    git rev-list "$spec" >>commit-only.baseline
+  done
+  for spec in "main^@" "other^@" "other~2^@"; do
+    echo "$spec" >>parents-only.baseline
+    echo $(git rev-list "$spec") >>parents-only.baseline
+  done
+)
+
+git clone -q --bare complex_graph complex_graph_bare
+(cd complex_graph_bare
+  baseline ":file"
+  baseline "@{-1}"
+  baseline "@:file"
+  baseline "main~1"
+)
+
+git init all_commits_match
+(cd all_commits_match
+  for n in 1 2 3; do git commit --allow-empty -q -m "release $n"; done
+
+  baseline ":/!-release"
+  baseline "@^{/!-release}"
+  baseline ":/!-nothing"
+)
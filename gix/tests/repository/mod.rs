@@ -46,6 +46,37 @@ mod dirwalk {
     }
 }
 
+#[cfg(feature = "excludes")]
+mod excludes {
+    use gix::config::tree::Core;
+    use gix_worktree::stack::state::ignore::Source;
+
+    #[test]
+    fn ignore_files_in_decomposed_directories_are_found_if_unicode_is_precomposed() -> crate::Result {
+        let (mut repo, _tmp) = crate::util::basic_rw_repo()?;
+        let mut index = gix::index::State::new(repo.object_hash());
+        index.dangerously_push_entry(
+            Default::default(),
+            repo.write_blob("*.o\n")?.detach(),
+            gix::index::entry::Flags::empty(),
+            gix::index::entry::Mode::FILE,
+            "a\u{308}/.gitignore".into(),
+        );
+
+        for precompose_unicode in [false, true] {
+            repo.config_snapshot_mut()
+                .set_value(&Core::PRECOMPOSE_UNICODE, precompose_unicode.to_string().as_str())?;
+            let mut excludes = repo.excludes(&index, None, Source::IdMapping)?;
+            assert_eq!(
+                excludes.at_entry("\u{e4}/file.o", Some(false))?.is_excluded(),
+                precompose_unicode,
+                "the decomposed path in the index matches the precomposed one only if `core.precomposeUnicode` is set"
+            );
+        }
+        Ok(())
+    }
+}
+
 #[test]
 fn size_in_memory() {
     let actual_size = std::mem::size_of::<Repository>();
@@ -283,6 +283,47 @@ mod object_caches {
     }
 }
 
+mod replacement_objects {
+    use crate::util::named_subrepo_opts;
+
+    fn message_of_head_commit(repo: &gix::Repository) -> crate::Result<String> {
+        Ok(repo.head_commit()?.message_raw()?.to_string())
+    }
+
+    #[test]
+    fn are_used_by_default() -> crate::Result {
+        let repo = named_subrepo_opts(
+            "make_rev_spec_parse_repos.sh",
+            "replaced",
+            gix::open::Options::isolated(),
+        )?;
+        assert_eq!(
+            message_of_head_commit(&repo)?,
+            "replacement\n",
+            "just like in git, replace refs are honored unless disabled"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn are_ignored_if_use_replace_refs_is_false() -> crate::Result {
+        let repo = named_subrepo_opts(
+            "make_rev_spec_parse_repos.sh",
+            "replaced",
+            gix::open::Options::isolated().config_overrides(["core.useReplaceRefs=false"]),
+        )?;
+        assert_eq!(message_of_head_commit(&repo)?, "original\n");
+
+        let repo = named_subrepo_opts(
+            "make_rev_spec_parse_repos.sh",
+            "replaced",
+            gix::open::Options::isolated().config_overrides(["core.useReplaceRefs=true"]),
+        )?;
+        assert_eq!(message_of_head_commit(&repo)?, "replacement\n");
+        Ok(())
+    }
+}
+
 mod worktree {
     use gix::open;
 
@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use crate::revision::spec::from_bytes::{parse_spec_no_baseline, repo};
+
+#[test]
+fn commits_of_all_spec_kinds_match_git_rev_list() -> crate::Result {
+    let repo = repo("complex_graph")?;
+    for (spec, expected) in [
+        ("c", &["C", "F", "I", "J"][..]),
+        ("^b", &[]),
+        ("b..a", &["A", "C"]),
+        ("a..b", &[]),
+        ("c...b", &["B", "C", "D", "E", "G", "H"]),
+        ("a^@", &["B", "C", "D", "E", "F", "G", "H", "I", "J"]),
+        ("b^!", &["B"]),
+        ("^a^@", &[]),
+    ] {
+        let mut actual = parse_spec_no_baseline(spec, &repo)?
+            .commits()?
+            .map(|info| -> crate::Result<_> { Ok(info?.object()?.message()?.summary().to_string()) })
+            .collect::<Result<Vec<_>, _>>()?;
+        actual.sort();
+        assert_eq!(actual, expected, "{spec}: the same commits as listed by `git log`");
+    }
+    Ok(())
+}
+
+#[test]
+fn parents_can_be_hidden_like_git_rev_list_does() -> crate::Result {
+    let repo = repo("complex_graph")?;
+    let a = parse_spec_no_baseline("a", &repo)?.single().expect("single commit");
+    let spec = parse_spec_no_baseline("^a^@", &repo)?;
+    assert_eq!(spec.kind(), gix_revision::spec::Kind::ExcludeReachableFromParentsOnly);
+
+    let mut hidden_tips = spec.hidden_tips()?;
+    hidden_tips.sort();
+    let mut parents: Vec<_> = a.object()?.into_commit().parent_ids().map(gix::Id::detach).collect();
+    parents.sort();
+    assert_eq!(
+        hidden_tips, parents,
+        "all parents of `a` are hidden, but not `a` itself"
+    );
+
+    let hidden: HashSet<_> = repo
+        .rev_walk(hidden_tips)
+        .all()?
+        .map(|info| info.map(|info| info.id))
+        .collect::<Result<_, _>>()?;
+    let actual = repo
+        .rev_walk(Some(a))
+        .selected(move |id| !hidden.contains(id))?
+        .map(|info| -> crate::Result<_> { Ok(info?.object()?.message()?.summary().to_string()) })
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(actual, ["A"], "the same as `git rev-list a ^a^@`");
+    Ok(())
+}
+
+#[test]
+fn criss_cross_merges_have_multiple_merge_bases() -> crate::Result {
+    let repo = repo("criss_cross")?;
+    let spec = parse_spec_no_baseline("main...other", &repo)?;
+    let mut actual: Vec<_> = spec
+        .merge_bases()?
+        .expect("a merge spec")
+        .into_iter()
+        .map(gix::Id::detach)
+        .collect();
+    actual.sort();
+
+    let baseline = std::fs::read_to_string(repo.work_dir().expect("non-bare").join("merge-bases.baseline"))?;
+    let mut expected = baseline
+        .lines()
+        .map(|hex| gix::ObjectId::from_hex(hex.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()?;
+    expected.sort();
+    assert_eq!(actual.len(), 2, "A1 and B1 are both best common ancestors");
+    assert_eq!(actual, expected, "the same as `git merge-base --all`");
+
+    let mut hidden_tips = spec.hidden_tips()?;
+    hidden_tips.sort();
+    assert_eq!(hidden_tips, expected, "merges hide their merge-bases");
+
+    for spec in ["main...main~1", "main~1...main"] {
+        let ancestor = parse_spec_no_baseline("main~1", &repo)?
+            .single()
+            .expect("single commit");
+        assert_eq!(
+            parse_spec_no_baseline(spec, &repo)?
+                .merge_bases()?
+                .expect("a merge spec"),
+            [ancestor],
+            "{spec}: a side that is an ancestor of the other is its only merge-base"
+        );
+    }
+
+    assert!(
+        parse_spec_no_baseline("main..other", &repo)?.merge_bases()?.is_none(),
+        "only merge specs have merge-bases"
+    );
+    Ok(())
+}
+
+#[test]
+fn commits_without_their_parents_are_just_the_commit() -> crate::Result {
+    let repo = repo("criss_cross")?;
+    let baseline = std::fs::read_to_string(repo.work_dir().expect("non-bare").join("commit-only.baseline"))?;
+    let mut lines = baseline.lines();
+    let mut count = 0;
+    while let Some(spec) = lines.next() {
+        let expected = gix::ObjectId::from_hex(lines.next().expect("one commit per spec").as_bytes())?;
+        let parsed = parse_spec_no_baseline(spec, &repo)?;
+        assert_eq!(*parsed, gix_revision::Spec::ExcludeParents(expected), "{spec}");
+
+        let actual = parsed
+            .commits()?
+            .map(|info| info.map(|info| info.id))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            actual,
+            [expected],
+            "{spec}: merges and roots alike yield only themselves, like `git rev-list` does"
+        );
+        count += 1;
+    }
+    assert_eq!(count, 3, "all specs were tested");
+    Ok(())
+}
+
+#[test]
+fn only_parents_are_walked_but_not_the_commit_itself() -> crate::Result {
+    let repo = repo("criss_cross")?;
+    let baseline = std::fs::read_to_string(repo.work_dir().expect("non-bare").join("parents-only.baseline"))?;
+    let mut lines = baseline.lines();
+    let mut count = 0;
+    while let Some(spec) = lines.next() {
+        let expected = lines
+            .next()
+            .expect("a line of commits per spec")
+            .split_whitespace()
+            .map(|hex| gix::ObjectId::from_hex(hex.as_bytes()))
+            .collect::<Result<HashSet<_>, _>>()?;
+        let parsed = parse_spec_no_baseline(spec, &repo)?;
+        let gix_revision::Spec::IncludeOnlyParents(commit) = *parsed else {
+            unreachable!("{spec}: only parents are included")
+        };
+        let parents: Vec<_> = repo
+            .find_object(commit)?
+            .into_commit()
+            .parent_ids()
+            .map(gix::Id::detach)
+            .collect();
+        assert_eq!(parsed.tips()?, parents, "{spec}: the walk starts at all parents");
+        assert!(parsed.hidden_tips()?.is_empty(), "{spec}: nothing is hidden");
+
+        let actual = parsed
+            .commits()?
+            .map(|info| info.map(|info| info.id))
+            .collect::<Result<HashSet<_>, _>>()?;
+        assert!(!actual.contains(&commit), "{spec}: the commit itself is never walked");
+        assert_eq!(actual, expected, "{spec}: the same commits as listed by `git rev-list`");
+        count += 1;
+    }
+    assert_eq!(
+        count, 3,
+        "all specs were tested, including a root commit without parents"
+    );
+    Ok(())
+}
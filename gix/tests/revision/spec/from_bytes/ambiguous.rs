@@ -1,7 +1,7 @@
 use gix::{
     prelude::{ObjectIdExt, RevSpecExt},
     revision::{
-        spec::parse::{Options, RefsHint},
+        spec::parse::{error::CandidateInfo, AmbiguousRefKind, Error, Options, RefsHint},
         Spec,
     },
 };
@@ -49,6 +49,88 @@ fn fully_failed_disambiguation_still_yields_an_ambiguity_error() {
     );
 }
 
+#[test]
+fn ambiguity_errors_can_summarize_all_candidates() {
+    let repo = repo("ambiguous_blob_tree_commit").unwrap();
+    let err = parse_spec_no_baseline_opts(
+        "0000000000",
+        &repo,
+        Options {
+            ambiguity_summaries: true,
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Short id 0000000000 is ambiguous. Candidates are:\n\t0000000000e commit 2005-04-07 \"a2onsxbvj\"\n\t0000000000c tree with 1 entry\n\t0000000000b blob of 30 bytes",
+        "trees and blobs are described as well"
+    );
+    match err {
+        gix::revision::spec::parse::Error::AmbiguousPrefix { info, .. } => {
+            assert!(
+                matches!(&info[0].1, CandidateInfo::Commit { title, .. } if title == "a2onsxbvj"),
+                "the commit is summarized by its subject line"
+            );
+        }
+        _ => unreachable!("ambiguity error expected"),
+    }
+}
+
+#[test]
+fn the_amount_of_candidates_can_be_limited() {
+    let repo = repo("ambiguous_commits").unwrap();
+    let opts = |max_prefix_candidates| Options {
+        max_prefix_candidates,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_spec_no_baseline_opts("0000", &repo, opts(Some(4)))
+            .unwrap_err()
+            .to_string(),
+        "Short id 0000 is ambiguous (more than 4 candidates)",
+        "the lookup stops early instead of collecting all 16 candidates"
+    );
+    assert!(
+        matches!(
+            parse_spec_no_baseline_opts("0000", &repo, opts(None)).unwrap_err(),
+            gix::revision::spec::parse::Error::AmbiguousPrefix { info, .. } if info.len() == 16
+        ),
+        "without a limit, all candidates are listed"
+    );
+    assert!(
+        parse_spec_no_baseline_opts("0000000005", &repo, opts(Some(1))).is_ok(),
+        "unambiguous prefixes are unaffected"
+    );
+}
+
+#[test]
+fn disambiguation_can_time_out() {
+    let repo = repo("ambiguous_commits").unwrap();
+    let opts = |disambiguation_budget| Options {
+        disambiguation_budget,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_spec_no_baseline_opts("0000", &repo, opts(Some(std::time::Duration::ZERO)))
+            .unwrap_err()
+            .to_string(),
+        "Looking up objects for short id 0000 took longer than allowed",
+        "without any budget, even the fastest object database is too slow"
+    );
+    assert!(
+        matches!(
+            parse_spec_no_baseline_opts("0000", &repo, opts(Some(std::time::Duration::from_secs(60 * 60)))).unwrap_err(),
+            gix::revision::spec::parse::Error::AmbiguousPrefix { info, .. } if info.len() == 16
+        ),
+        "with enough time, all candidates are found"
+    );
+    assert!(
+        parse_spec_no_baseline_opts("0000000005", &repo, opts(Some(std::time::Duration::from_secs(60 * 60)))).is_ok(),
+        "unambiguous prefixes are found as usual"
+    );
+}
+
 #[test]
 fn ranges_are_auto_disambiguated_by_committish() {
     let repo = repo("ambiguous_blob_tree_commit").unwrap();
@@ -69,6 +151,30 @@ fn ranges_are_auto_disambiguated_by_committish() {
     }
 }
 
+#[test]
+fn committish_positions_leave_no_ambiguity_for_later_errors() {
+    let repo = repo("ambiguous_blob_tree_commit").unwrap();
+    let id = hex_to_id("0000000000e4f9fbd19cf1e932319e5ad0d1d00b");
+    assert_eq!(
+        parse_spec_no_baseline("^000000000", &repo).unwrap(),
+        gix_revision::Spec::Exclude(id).attach(&repo),
+        "exclusions need a commit, which is known before the prefix is seen"
+    );
+
+    for spec in ["000000000..missing", "000000000...missing"] {
+        let err = parse_spec_no_baseline(spec, &repo).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The right endpoint of the range could not be resolved",
+            "{spec}: the left side was disambiguated, and the error is only about the right side"
+        );
+        assert_eq!(
+            std::error::Error::source(&err).expect("source").to_string(),
+            "The ref partially named \"missing\" could not be found"
+        );
+    }
+}
+
 #[test]
 fn blob_and_tree_can_be_disambiguated_by_type() {
     let repo = repo("ambiguous_blob_tree_commit").unwrap();
@@ -129,6 +235,16 @@ fn commits_can_be_disambiguated_with_commit_specific_transformations() {
     }
 }
 
+#[test]
+fn commits_can_be_disambiguated_by_their_position_in_describe_output() {
+    let repo = repo("ambiguous_blob_tree_commit").unwrap();
+    assert_eq!(
+        parse_spec("x-g0000000000", &repo).unwrap(),
+        Spec::from_id(hex_to_id("0000000000e4f9fbd19cf1e932319e5ad0d1d00b").attach(&repo)),
+        "the prefix also matches a blob and a tree, but describe output can only refer to commits"
+    );
+}
+
 #[test]
 fn tags_can_be_disambiguated_with_commit_specific_transformations() {
     let repo = repo("ambiguous_commits").unwrap();
@@ -153,6 +269,7 @@ fn opts_ref_hint(hint: RefsHint) -> Options {
     Options {
         refs_hint: hint,
         object_kind_hint: None,
+        ..Default::default()
     }
 }
 
@@ -186,7 +303,7 @@ fn ambiguous_40hex_refs_are_ignored_and_we_prefer_the_object_of_the_same_name()
         )
             .unwrap_err()
             .to_string(),
-        "The short hash 0000000000e4f9fbd19cf1e932319e5ad0d1d00b matched both the reference refs/heads/0000000000e4f9fbd19cf1e932319e5ad0d1d00b and at least one object"
+        "The short hash 0000000000e4f9fbd19cf1e932319e5ad0d1d00b matched both the branch refs/heads/0000000000e4f9fbd19cf1e932319e5ad0d1d00b and at least one object"
     );
 }
 
@@ -216,11 +333,37 @@ fn ambiguous_short_refs_are_dereferenced() {
         parse_spec_no_baseline_opts(spec, &repo, opts_ref_hint(RefsHint::Fail))
             .unwrap_err()
             .to_string(),
-        "The short hash 0000000000e matched both the reference refs/heads/0000000000e and at least one object",
+        "The short hash 0000000000e matched both the branch refs/heads/0000000000e and at least one object",
         "users who don't want this ambiguity, could fail like this."
     );
 }
 
+#[test]
+fn ambiguous_tags_report_their_kind() -> crate::Result {
+    let repo = repo("ambiguous_tags")?;
+    for (spec, expected_kind) in [
+        ("0000000000e4", AmbiguousRefKind::LightweightTag),
+        ("0000000000e4f", AmbiguousRefKind::AnnotatedTag),
+    ] {
+        let err = parse_spec_no_baseline_opts(spec, &repo, opts_ref_hint(RefsHint::Fail)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("The short hash {spec} matched both the {expected_kind} refs/tags/{spec} and at least one object")
+        );
+        let Error::Multi { current, .. } = err else {
+            panic!("the ambiguous objects are listed as well")
+        };
+        assert!(
+            matches!(
+                current.downcast_ref::<Error>(),
+                Some(Error::AmbiguousRefAndObject { kind, .. }) if *kind == expected_kind
+            ),
+            "{spec}: the kind is available for programmatic use, too"
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn repository_local_disambiguation_hints_disambiguate() {
     let r = repo("ambiguous_objects_disambiguation_config_committish").unwrap();
@@ -1,4 +1,7 @@
-use gix::{prelude::ObjectIdExt, revision::Spec};
+use gix::{
+    prelude::ObjectIdExt,
+    revision::{spec::parse::Warning, Spec},
+};
 pub use util::*;
 
 use crate::util::hex_to_id;
@@ -35,13 +38,49 @@ mod sibling_branch {
         }
         Ok(())
     }
+
+    #[test]
+    fn push_in_triangular_workflow_depends_on_push_default() -> crate::Result {
+        for (push_default, expected_push) in [
+            ("current", Some("refs/remotes/fork/feature")),
+            ("upstream", Some("refs/remotes/origin/main")),
+            ("nothing", None),
+        ] {
+            let repo = repo(&format!("triangular-push-{push_default}"))?;
+            assert_eq!(
+                parse_spec("feature@{upstream}", &repo)?
+                    .first_reference()
+                    .expect("set")
+                    .name
+                    .as_bstr(),
+                "refs/remotes/origin/main",
+                "{push_default}: the upstream is always fetched from `origin`"
+            );
+            match expected_push {
+                Some(expected) => assert_eq!(
+                    parse_spec("feature@{push}", &repo)?
+                        .first_reference()
+                        .expect("set")
+                        .name
+                        .as_bstr(),
+                    expected,
+                    "{push_default}: pushes go to `fork` unless the upstream is used"
+                ),
+                None => assert_eq!(
+                    parse_spec("feature@{push}", &repo).unwrap_err().to_string(),
+                    "Branch named refs/heads/feature does not have a push destination configured"
+                ),
+            }
+        }
+        Ok(())
+    }
 }
 
 mod index {
     use gix::{prelude::ObjectIdExt, revision::Spec};
 
     use crate::{
-        revision::spec::from_bytes::{parse_spec, repo},
+        revision::spec::from_bytes::{parse_spec, parse_spec_no_baseline, parse_spec_no_baseline_opts, repo},
         util::hex_to_id,
     };
 
@@ -69,6 +108,100 @@ mod index {
             "Path \"foo\" did not exist in index at stage 0. It does not exist on disk",
         );
     }
+
+    #[test]
+    fn path_prefixes() -> crate::Result {
+        let repo = repo("complex_graph")?;
+        let expected = parse_spec(":file", &repo)?;
+        assert_eq!(parse_spec_no_baseline(":./file", &repo)?, expected);
+        assert_eq!(parse_spec_no_baseline(":0:./dir/../file", &repo)?, expected);
+        assert_eq!(
+            parse_spec_no_baseline(":../file", &repo).unwrap_err().to_string(),
+            "Path \"../file\" leads outside of the tree as it has more '..' components than there are directories to leave"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn without_index() -> crate::Result {
+        let bare = repo("complex_graph_bare")?;
+        assert!(bare.is_bare());
+        assert_eq!(
+            parse_spec(":file", &bare).unwrap_err().to_string(),
+            "Path \"file\" cannot be looked up in the index as the repository is bare and has no index file"
+        );
+        assert_eq!(
+            parse_spec("@:file", &bare)?,
+            parse_spec_no_baseline(":file", &repo("complex_graph")?)?,
+            "trees can still be used to find the same blob"
+        );
+
+        let new = repo("new")?;
+        assert_eq!(
+            parse_spec_no_baseline(":file", &new).unwrap_err().to_string(),
+            "Path \"file\" cannot be looked up in the index as there is no index file yet"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn with_alternate_index() -> crate::Result {
+        let non_bare = repo("complex_graph")?;
+        let tree = non_bare.rev_parse_single("b^{tree}")?;
+        let index = non_bare.index_from_tree(&tree)?;
+        let actual = Spec::from_bstr_with_index(":file", &non_bare, &index, Default::default())?;
+        assert_eq!(
+            actual,
+            Spec::from_id(hex_to_id("a4e7183785db249c16b8d20b77f2ffc3c4c666fd").attach(&non_bare)),
+            "the file as it is in `b`, not as in the index of the repository"
+        );
+        assert_ne!(actual, parse_spec(":file", &non_bare)?);
+        assert_eq!(
+            Spec::from_bstr_with_index(":1:file", &non_bare, &index, Default::default())
+                .unwrap_err()
+                .to_string(),
+            "Path \"file\" did not exist in index at stage 1. It does exist at stage 0. It exists on disk",
+        );
+
+        let bare = repo("complex_graph_bare")?;
+        assert_eq!(
+            Spec::from_bstr_with_index(":file", &bare, &index, Default::default())?,
+            actual,
+            "bare repositories can use an index if one is provided"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn blob_size_on_request() -> crate::Result {
+        let repo = repo("complex_graph").unwrap();
+        let actual = parse_spec_no_baseline_opts(
+            ":file",
+            &repo,
+            gix::revision::spec::parse::Options {
+                blob_size: true,
+                ..Default::default()
+            },
+        )?;
+        let expected_size = std::fs::metadata(repo.work_dir().expect("non-bare").join("file"))?.len();
+        assert_eq!(actual.blob_size(), Some(expected_size), "the header provides the size");
+
+        assert_eq!(
+            parse_spec(":file", &repo)?.blob_size(),
+            None,
+            "the size isn't obtained unless asked for"
+        );
+        let actual = parse_spec_no_baseline_opts(
+            "@",
+            &repo,
+            gix::revision::spec::parse::Options {
+                blob_size: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(actual.blob_size(), None, "only blobs have their size reported");
+        Ok(())
+    }
 }
 
 #[test]
@@ -95,6 +228,469 @@ fn names_are_made_available_via_references() {
     );
 }
 
+#[test]
+fn at_is_shorthand_for_head() -> crate::Result {
+    let repo = repo("complex_graph")?;
+    for (at_spec, head_spec) in [
+        ("@", "HEAD"),
+        ("@~1", "HEAD~1"),
+        ("@^2", "HEAD^2"),
+        ("@{1}", "HEAD@{1}"),
+    ] {
+        let actual = parse_spec_no_baseline(at_spec, &repo)?;
+        assert_eq!(
+            actual,
+            parse_spec_no_baseline(head_spec, &repo)?,
+            "{at_spec} == {head_spec}"
+        );
+        assert_eq!(
+            actual.first_reference().map(|r| r.name.as_bstr().to_string()),
+            Some(if at_spec == "@{1}" { "refs/heads/main" } else { "HEAD" }.into()),
+            "{at_spec}: `@` is bound to HEAD, but the reflog of the branch HEAD points to is used"
+        );
+    }
+    assert_eq!(
+        parse_spec_no_baseline("main@", &repo).unwrap_err().to_string(),
+        "The ref partially named \"main@\" could not be found",
+        "trailing `@` are part of the ref name"
+    );
+    Ok(())
+}
+
+#[test]
+fn ambiguous_reference_names_provide_all_candidates_on_request() -> crate::Result {
+    use gix::refs::transaction::PreviousValue;
+    let (repo, _tmp) = repo_rw("complex_graph")?;
+    let main_id = repo.find_reference("main")?.id().detach();
+    let g_id = repo.find_reference("g")?.id().detach();
+    repo.reference(
+        "refs/heads/origin",
+        g_id,
+        PreviousValue::MustNotExist,
+        "branch named like remote",
+    )?;
+    repo.reference(
+        "refs/remotes/origin/HEAD",
+        main_id,
+        PreviousValue::MustNotExist,
+        "remote HEAD",
+    )?;
+
+    let spec = parse_spec_no_baseline("origin", &repo)?;
+    assert_eq!(spec.single().expect("single"), g_id, "like git, local branches win");
+    assert_eq!(
+        spec.warnings(),
+        [Warning::AmbiguousReferenceName {
+            name: "origin".into(),
+            candidates: vec!["refs/heads/origin".into(), "refs/remotes/origin/HEAD".into()],
+        }],
+        "ambiguity is always reported, like git does"
+    );
+    assert_eq!(
+        spec.ref_candidates(),
+        (&[][..], &[][..]),
+        "candidates aren't collected by default"
+    );
+
+    let spec = parse_spec_no_baseline_opts(
+        "origin..main",
+        &repo,
+        gix::revision::spec::parse::Options {
+            ref_candidates: true,
+            ..Default::default()
+        },
+    )?;
+    let (first, second) = spec.ref_candidates();
+    assert_eq!(
+        first.iter().map(|r| r.name.as_bstr().to_string()).collect::<Vec<_>>(),
+        ["refs/heads/origin", "refs/remotes/origin/HEAD"],
+        "all candidates are listed in order of precedence"
+    );
+    assert!(second.is_empty(), "unambiguous names have no candidates");
+    assert_eq!(
+        spec.first_reference().expect("set").name.as_bstr(),
+        "refs/heads/origin",
+        "the first candidate is the one that was used"
+    );
+    Ok(())
+}
+
+#[test]
+fn reference_names_are_expanded_like_git_does() -> crate::Result {
+    let repo = repo("complex_graph").unwrap();
+    for (name, expected) in [
+        ("HEAD", "HEAD"),
+        ("refs/heads/main", "refs/heads/main"),
+        ("heads/main", "refs/heads/main"),
+        ("i-tag", "refs/tags/i-tag"),
+        ("main", "refs/heads/main"),
+        ("origin/main", "refs/remotes/origin/main"),
+    ] {
+        let spec = parse_spec_no_baseline(name, &repo)?;
+        assert_eq!(spec.first_reference().expect("set").name.as_bstr(), expected, "{name}");
+        assert_eq!(spec.warnings(), [], "{name}: unambiguous names don't cause warnings");
+    }
+    Ok(())
+}
+
+#[test]
+fn tags_win_over_branches_of_the_same_name() -> crate::Result {
+    use gix::refs::transaction::PreviousValue;
+    let (repo, _tmp) = repo_rw("complex_graph")?;
+    let g_id = repo.find_reference("g")?.id().detach();
+    repo.reference(
+        "refs/tags/main",
+        g_id,
+        PreviousValue::MustNotExist,
+        "tag named like branch",
+    )?;
+
+    let spec = parse_spec_no_baseline("main", &repo)?;
+    assert_eq!(spec.first_reference().expect("set").name.as_bstr(), "refs/tags/main");
+    assert_eq!(spec.single().expect("single"), g_id);
+    assert_eq!(
+        spec.warnings(),
+        [Warning::AmbiguousReferenceName {
+            name: "main".into(),
+            candidates: vec!["refs/tags/main".into(), "refs/heads/main".into()],
+        }]
+    );
+    assert_eq!(
+        spec.warnings()[0].to_string(),
+        "Reference name \"main\" is ambiguous and was resolved to \"refs/tags/main\""
+    );
+    Ok(())
+}
+
+#[test]
+fn reference_names_can_be_matched_ignoring_case() -> crate::Result {
+    use gix::refs::transaction::PreviousValue;
+    let (repo, _tmp) = repo_rw("complex_graph")?;
+    let opts = gix::revision::spec::parse::Options {
+        refs_ignore_case: true,
+        ..Default::default()
+    };
+    assert!(
+        parse_spec_no_baseline("MAIN", &repo).is_err(),
+        "names must match exactly by default"
+    );
+    for (name, expected) in [("MAIN", "refs/heads/main"), ("I-Tag", "refs/tags/i-tag")] {
+        let spec = parse_spec_no_baseline_opts(name, &repo, opts)?;
+        assert_eq!(spec.first_reference().expect("set").name.as_bstr(), expected, "{name}");
+    }
+
+    let g_id = repo.find_reference("g")?.id().detach();
+    repo.reference("refs/heads/Main", g_id, PreviousValue::MustNotExist, "differs by case")?;
+    assert_eq!(
+        parse_spec_no_baseline_opts("MAIN", &repo, opts)
+            .unwrap_err()
+            .to_string(),
+        "Reference name \"MAIN\" is ambiguous when ignoring case, candidates are: refs/heads/Main, refs/heads/main"
+    );
+    for name in ["main", "Main"] {
+        assert_eq!(
+            parse_spec_no_baseline_opts(name, &repo, opts)?
+                .first_reference()
+                .expect("set")
+                .name
+                .as_bstr(),
+            format!("refs/heads/{name}"),
+            "exact matches are always preferred"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn names_are_canonical() -> crate::Result {
+    let repo = repo("complex_graph")?;
+    let short_id =
+        |spec: &str| -> crate::Result<String> { Ok(repo.rev_parse_single(spec)?.shorten_or_id().to_string()) };
+    let main_parent = short_id("main~1")?;
+    let file = short_id("main:file")?;
+    for (spec, expected) in [
+        ("main", "main".to_string()),
+        ("@", "HEAD".into()),
+        ("^b", "^b".into()),
+        ("b..a", "b..a".into()),
+        ("..a", "HEAD..a".into()),
+        ("c...b", "c...b".into()),
+        ("a^@", "a^@".into()),
+        ("b^!", "b^!".into()),
+        ("^a^@", "^a^@".into()),
+        ("i-tag", "i-tag".into()),
+        ("i-tag^{commit}", "i-tag^{commit}".into()),
+        ("main~1", main_parent.clone()),
+        ("main~1..main", format!("{main_parent}..main")),
+        ("main:file", file.clone()),
+    ] {
+        let parsed = parse_spec_no_baseline(spec, &repo)?;
+        let name = parsed.name();
+        assert_eq!(name, expected, "{spec}");
+        assert_eq!(
+            parse_spec_no_baseline(&name.to_string(), &repo)?,
+            parsed,
+            "{spec}: the name resolves to the same spec"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn kind_predicates() -> crate::Result {
+    let repo = repo("complex_graph").unwrap();
+    for (spec, is_single, is_range, is_merge) in [
+        ("a", true, false, false),
+        ("^a", true, false, false),
+        ("a^!", true, false, false),
+        ("a^@", true, false, false),
+        ("^a^@", true, false, false),
+        ("a..b", false, true, false),
+        ("a...b", false, false, true),
+    ] {
+        let spec_obj = parse_spec_no_baseline(spec, &repo)?;
+        assert_eq!(
+            (spec_obj.is_single(), spec_obj.is_range(), spec_obj.is_merge()),
+            (is_single, is_range, is_merge),
+            "{spec}"
+        );
+        assert_eq!(
+            spec_obj.single_object().is_ok(),
+            is_single,
+            "{spec}: single specs are those with a single object"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn single_object_rejects_ranges() -> crate::Result {
+    let repo = repo("complex_graph").unwrap();
+    let a = parse_spec("a", &repo)?.single().expect("single");
+    for spec in ["a", "^a", "a^!", "a^@"] {
+        assert_eq!(
+            parse_spec_no_baseline(spec, &repo)?.single_object()?,
+            a,
+            "{spec}: all single-object forms yield their object"
+        );
+    }
+    for spec in ["a..b", "a...b"] {
+        let err = parse_spec_no_baseline(spec, &repo)?.single_object().unwrap_err();
+        assert!(
+            matches!(err, gix::revision::spec::parse::Error::RangeNotAllowed { .. }),
+            "{spec}: ranges are rejected, got {err:?}"
+        );
+    }
+    assert_eq!(
+        parse_spec_no_baseline("a..b", &repo)?.single_object().unwrap_err().to_string(),
+        "Spec 55e825ebe8fd2ff78cad3826afb696b96b576a7e..5b3f9e24965d0b28780b7ce5daf2b5b7f7e0459f is a range, but a single object was expected"
+    );
+    Ok(())
+}
+
+#[test]
+fn single_reference_distinguishes_references_from_objects() -> crate::Result {
+    use gix::revision::spec::parse::Error;
+    let repo = repo("complex_graph").unwrap();
+    for spec in ["main", "refs/heads/main", "main^{commit}"] {
+        assert_eq!(
+            parse_spec_no_baseline(spec, &repo)?.single_reference()?.name.as_bstr(),
+            "refs/heads/main",
+            "{spec}: the reference can be used as is"
+        );
+    }
+
+    let id = repo.rev_parse_single("main")?.to_string();
+    for spec in [id.as_str(), &id[..7], "main~1", "main:file"] {
+        let err = parse_spec_no_baseline(spec, &repo)?.single_reference().unwrap_err();
+        assert!(
+            matches!(err, Error::NotAReference { .. }),
+            "{spec}: hashes and objects found through references aren't references, got {err:?}"
+        );
+    }
+    assert_eq!(
+        parse_spec_no_baseline(&id[..7], &repo)?
+            .single_reference()
+            .unwrap_err()
+            .to_string(),
+        format!("Spec {id} doesn't name a reference, but only an object")
+    );
+    assert!(matches!(
+        parse_spec_no_baseline("a..b", &repo)?.single_reference().unwrap_err(),
+        Error::RangeNotAllowed { .. }
+    ));
+    Ok(())
+}
+
+#[test]
+fn keys_ignore_the_repository_and_identify_equal_resolutions() -> crate::Result {
+    use std::collections::HashSet;
+    let repo = repo("complex_graph").unwrap();
+    let head = parse_spec_no_baseline("HEAD", &repo)?.key();
+    assert_eq!(
+        head,
+        parse_spec_no_baseline("HEAD", &repo)?.key(),
+        "resolving the same spec twice yields the same key"
+    );
+    assert_eq!(head, parse_spec_no_baseline("@", &repo)?.key(), "`@` is HEAD");
+    assert_eq!(
+        head.first_ref.as_ref().map(|n| n.as_bstr().to_string()),
+        Some("HEAD".into())
+    );
+
+    let id = repo.head_id()?.to_string();
+    let by_id = parse_spec_no_baseline(&id, &repo)?.key();
+    assert_eq!(by_id.spec, head.spec, "both resolve to the same object…");
+    assert_ne!(by_id, head, "…but only one of them used a reference");
+
+    let unique: HashSet<_> = ["HEAD", "@", "main", &id, "a..b", "HEAD"]
+        .into_iter()
+        .map(|spec| parse_spec_no_baseline(spec, &repo).map(|spec| spec.key()))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(
+        unique.len(),
+        4,
+        "HEAD and @ are deduplicated, but main refers to another reference"
+    );
+    Ok(())
+}
+
+#[test]
+fn ref_completions_list_matching_branches_remote_branches_and_tags() -> crate::Result {
+    use gix::refs::Category;
+    let repo = repo("complex_graph").unwrap();
+    let completions = |partial: &str| -> crate::Result<Vec<(String, Category<'static>, String)>> {
+        Ok(repo
+            .rev_parse_ref_completions(partial)?
+            .into_iter()
+            .map(|c| (c.name.as_bstr().to_string(), c.category, c.completion.to_string()))
+            .collect())
+    };
+    assert_eq!(
+        completions("mai")?,
+        [("refs/heads/main".into(), Category::LocalBranch, "main".into())],
+        "short names are matched"
+    );
+    assert_eq!(
+        completions("b")?,
+        [
+            ("refs/heads/b".into(), Category::LocalBranch, "b".into()),
+            ("refs/tags/b-tag".into(), Category::Tag, "b-tag".into())
+        ],
+        "candidates of all categories are ordered by full name"
+    );
+    assert_eq!(
+        completions("origin/ma")?,
+        [(
+            "refs/remotes/origin/main".into(),
+            Category::RemoteBranch,
+            "origin/main".into()
+        )]
+    );
+    assert_eq!(
+        completions("refs/tags/")?,
+        [
+            ("refs/tags/b-tag".into(), Category::Tag, "refs/tags/b-tag".into()),
+            ("refs/tags/i-tag".into(), Category::Tag, "refs/tags/i-tag".into())
+        ],
+        "full names complete to full names"
+    );
+    assert_eq!(
+        completions("tags/i")?,
+        [("refs/tags/i-tag".into(), Category::Tag, "tags/i-tag".into())],
+        "just like names without `refs/`"
+    );
+    assert_eq!(
+        completions("HEA")?,
+        [],
+        "only branches, remote branches and tags are candidates"
+    );
+    assert_eq!(completions("")?.len(), 24, "nothing typed yet matches everything");
+    Ok(())
+}
+
+#[test]
+fn object_is_looked_up_for_single_object_specs() -> crate::Result {
+    let repo = repo("complex_graph").unwrap();
+    let head = parse_spec_no_baseline("HEAD", &repo)?.object()?;
+    assert_eq!(head.kind, gix::object::Kind::Commit, "HEAD points to a commit");
+    assert_eq!(head.id, repo.head_id()?, "it's the object HEAD points to");
+
+    let err = parse_spec_no_baseline("a..b", &repo)?.object().unwrap_err();
+    assert!(
+        matches!(err, gix::revision::spec::parse::Error::RangeNotAllowed { .. }),
+        "ranges can't be turned into a single object, got {err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn failing_range_endpoints_are_identified() -> crate::Result {
+    use gix::revision::spec::parse::Error;
+    let repo = repo("complex_graph")?;
+    for (spec, expected) in [
+        (
+            "doesnotexist..HEAD",
+            "The left endpoint of the range could not be resolved",
+        ),
+        (
+            "doesnotexist~1...HEAD",
+            "The left endpoint of the range could not be resolved",
+        ),
+        (
+            "HEAD..doesnotexist",
+            "The right endpoint of the range could not be resolved",
+        ),
+        (
+            "HEAD...doesnotexist",
+            "The right endpoint of the range could not be resolved",
+        ),
+        (
+            "..doesnotexist",
+            "The right endpoint of the range could not be resolved",
+        ),
+    ] {
+        let err = parse_spec_no_baseline(spec, &repo).unwrap_err();
+        assert_eq!(err.to_string(), expected, "{spec}");
+        let (Error::LeftEndpoint { source } | Error::RightEndpoint { source }) = err else {
+            unreachable!("checked above")
+        };
+        assert_eq!(
+            source.to_string(),
+            "The ref partially named \"doesnotexist\" could not be found",
+            "{spec}: the actual reason is the source"
+        );
+    }
+    assert_eq!(
+        parse_spec_no_baseline("doesnotexist", &repo).unwrap_err().to_string(),
+        "The ref partially named \"doesnotexist\" could not be found",
+        "errors for non-ranges are unchanged"
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_can_be_inspected_before_the_delegate_is_consumed() -> crate::Result {
+    use gix::revision::spec::parse::{Delegate, Error};
+    let repo = repo("complex_graph")?;
+
+    let mut delegate = Delegate::new(&repo, Default::default());
+    gix::revision::plumbing::spec::parse("main".into(), &mut delegate)?;
+    assert!(delegate.errors().is_empty());
+    assert!(!delegate.has_fatal(), "a reference was found");
+    assert!(delegate.into_rev_spec().is_ok());
+
+    let mut delegate = Delegate::new(&repo, Default::default());
+    gix::revision::plumbing::spec::parse("does-not-exist".into(), &mut delegate).ok();
+    assert_eq!(delegate.errors().len(), 1, "the failed lookup is recorded");
+    assert!(delegate.has_fatal(), "there is nothing to continue with");
+    assert!(
+        matches!(delegate.into_err(), Error::FindReference(_)),
+        "the final error is still available"
+    );
+    Ok(())
+}
+
 #[test]
 fn bad_objects_are_valid_until_they_are_actually_read_from_the_odb() {
     {
@@ -142,6 +738,20 @@ fn access_blob_through_tree() {
         parse_spec("0000000000cdc:missing", &repo).unwrap_err().to_string(),
         "Could not find path \"missing\" in tree 0000000000c of parent object 0000000000c"
     );
+
+    assert_eq!(
+        parse_spec_no_baseline("0000000000cdc:a0blgqsjc/oops", &repo)
+            .unwrap_err()
+            .to_string(),
+        "Path component \"a0blgqsjc\" of path \"a0blgqsjc/oops\" is not a tree in tree 0000000000c of parent object 0000000000c"
+    );
+    assert_eq!(
+        parse_spec_no_baseline("0000000000cdc:missing/oops", &repo)
+            .unwrap_err()
+            .to_string(),
+        "Could not find path \"missing/oops\" in tree 0000000000c of parent object 0000000000c",
+        "genuinely missing paths are still reported as such"
+    );
 }
 
 #[test]
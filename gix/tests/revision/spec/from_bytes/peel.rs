@@ -1,7 +1,7 @@
 use gix::{prelude::ObjectIdExt, revision::Spec};
 
 use crate::{
-    revision::spec::from_bytes::{parse_spec, repo},
+    revision::spec::from_bytes::{parse_spec, parse_spec_no_baseline, repo},
     util::hex_to_id,
 };
 
@@ -18,6 +18,30 @@ fn peel_to_object() {
     );
 }
 
+#[test]
+fn peel_to_tag_stops_at_the_annotated_tag() -> crate::Result {
+    let repo = &repo("complex_graph")?;
+    let tag_id = repo.find_reference("b-tag")?.target().id().to_owned();
+    assert_eq!(repo.find_object(tag_id)?.kind, gix::object::Kind::Tag);
+
+    assert_eq!(
+        parse_spec("b-tag^{tag}", repo)?,
+        Spec::from_id(tag_id.attach(repo)),
+        "the tag object itself is returned"
+    );
+    assert_eq!(
+        parse_spec("b-tag^{commit}", repo)?,
+        Spec::from_id(hex_to_id("5b3f9e24965d0b28780b7ce5daf2b5b7f7e0459f").attach(repo)),
+        "other kinds see through the tag"
+    );
+    assert_eq!(
+        parse_spec("main^{tag}", repo).unwrap_err().to_string(),
+        "Last encountered object 55e825e was commit while trying to peel to tag",
+        "commits can't be peeled into tags"
+    );
+    Ok(())
+}
+
 #[test]
 fn trailing_colon_is_equivalent_to_peel_to_tree() {
     let repo = &repo("complex_graph").unwrap();
@@ -25,3 +49,203 @@ fn trailing_colon_is_equivalent_to_peel_to_tree() {
     assert_eq!(parse_spec("@^{tree}", repo).unwrap(), empty_path);
     assert_eq!(empty_path.path_and_mode(), None, "empty tree paths are ignored");
 }
+
+#[test]
+fn paths_with_trailing_slash_resolve_to_trees() -> crate::Result {
+    let repo = &repo("nested_tree")?;
+    let root = parse_spec("@:", repo)?;
+    assert_eq!(
+        root.single().expect("single").object()?.kind,
+        gix::object::Kind::Tree,
+        "empty paths resolve to the root tree"
+    );
+
+    let src = parse_spec("@:src/", repo)?;
+    assert_eq!(src.single().expect("single").object()?.kind, gix::object::Kind::Tree);
+    assert_eq!(
+        src,
+        parse_spec("@:src", repo)?,
+        "a trailing slash doesn't change the result"
+    );
+    assert_eq!(
+        src.path_and_mode().expect("set").1,
+        gix_object::tree::EntryKind::Tree.into()
+    );
+    assert_eq!(
+        parse_spec("@:src/sub/", repo)?.single().expect("single").object()?.kind,
+        gix::object::Kind::Tree
+    );
+
+    assert_eq!(
+        parse_spec("@:src/lib.rs", repo)?
+            .single()
+            .expect("single")
+            .object()?
+            .kind,
+        gix::object::Kind::Blob
+    );
+    assert_eq!(
+        parse_spec("@:src/lib.rs/", repo).unwrap_err().to_string(),
+        "Path component \"src/lib.rs\" of path \"src/lib.rs/\" is not a tree in tree 6eb375a of parent object f15d76f",
+        "a trailing slash requires a tree, just like in git"
+    );
+    Ok(())
+}
+
+#[test]
+fn paths_can_be_followed_by_a_kind_assertion() -> crate::Result {
+    let repo = &repo("nested_tree")?;
+    let src = parse_spec_no_baseline("HEAD:src^{tree}", repo)?;
+    assert_eq!(src, parse_spec("@:src", repo)?, "it's the subtree at `src`");
+    assert_eq!(
+        src.path_and_mode().expect("set").0,
+        "src",
+        "the kind isn't part of the recorded path"
+    );
+    assert_eq!(
+        parse_spec_no_baseline("HEAD:src/lib.rs^{blob}", repo)?,
+        parse_spec("@:src/lib.rs", repo)?
+    );
+
+    assert_eq!(
+        parse_spec_no_baseline("HEAD:src/lib.rs^{tree}", repo)
+            .unwrap_err()
+            .to_string(),
+        "Last encountered object a65b417 was blob while trying to peel to tree",
+        "the kind of the entry is asserted"
+    );
+    assert_eq!(
+        parse_spec_no_baseline("HEAD:missing^{tree}", repo)
+            .unwrap_err()
+            .to_string(),
+        "Could not find path \"missing^{tree}\" in tree 6eb375a of parent object f15d76f",
+        "like in git, paths that don't exist are reported as given"
+    );
+    Ok(())
+}
+
+#[test]
+fn path_prefixes_are_normalized() -> crate::Result {
+    let repo = &repo("nested_tree")?;
+    assert_eq!(repo.prefix()?, None, "the tests run outside of the worktree");
+    let lib = parse_spec("@:src/lib.rs", repo)?;
+
+    let root_relative = parse_spec_no_baseline("@:/src/lib.rs", repo)?;
+    assert_eq!(root_relative, lib, "a leading slash addresses the root of the tree");
+    assert_eq!(
+        root_relative.path_and_mode().expect("set").0,
+        "src/lib.rs",
+        "paths are recorded relative to the root"
+    );
+    assert_eq!(
+        parse_spec_no_baseline("@:/", repo)?,
+        parse_spec("@:", repo)?,
+        "a single slash is the root tree"
+    );
+
+    assert_eq!(
+        parse_spec_no_baseline("@:./src/lib.rs", repo)?,
+        lib,
+        "without a prefix, the current directory is the root"
+    );
+    assert_eq!(parse_spec_no_baseline("@:./src/sub/../lib.rs", repo)?, lib);
+    assert_eq!(parse_spec_no_baseline("@:./src/./lib.rs", repo)?, lib);
+    assert_eq!(
+        parse_spec_no_baseline("@:./src/sub/..", repo)?,
+        parse_spec("@:src", repo)?,
+        "navigating up can end in a directory"
+    );
+    assert_eq!(parse_spec_no_baseline("@:.", repo)?, parse_spec("@:", repo)?);
+
+    for spec in ["@:..", "@:../src/lib.rs", "@:./src/../../src/lib.rs"] {
+        assert_eq!(
+            parse_spec_no_baseline(spec, repo).unwrap_err().to_string(),
+            format!(
+                "Path {:?} leads outside of the tree as it has more '..' components than there are directories to leave",
+                &spec[2..]
+            ),
+            "escaping the tree is an error"
+        );
+    }
+
+    assert!(
+        parse_spec_no_baseline("@:src/../src/lib.rs", repo).is_err(),
+        "paths that aren't cwd-relative are taken as is, and there is no '..' entry in trees"
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_objects_can_be_fetched_while_peeling() -> crate::Result {
+    use gix::revision::spec::parse::{Error, Options};
+    let (repo, _tmp) = crate::util::basic_rw_repo()?;
+    let tree_id = repo.head_commit()?.tree_id()?.detach();
+    let hex = tree_id.to_string();
+    let loose_tree = repo.path().join("objects").join(&hex[..2]).join(&hex[2..]);
+    let loose_tree_data = std::fs::read(&loose_tree)?;
+    std::fs::remove_file(&loose_tree)?;
+
+    let opts = Options {
+        fetch_missing_objects: true,
+        ..Default::default()
+    };
+    let err = Spec::from_bstr("HEAD^{tree}", &repo, opts).unwrap_err();
+    assert!(
+        matches!(err, Error::PeelToKind(_)),
+        "without a way to fetch it, the object stays missing: {err:?}"
+    );
+
+    let mut fetch_fails = |_: &gix_hash::oid| Err("remote unavailable".into());
+    let err = Spec::from_bstr_with_fetch("HEAD^{tree}", &repo, &mut fetch_fails, opts).unwrap_err();
+    assert_eq!(err.to_string(), format!("Could not fetch missing object {hex}"));
+
+    let mut fetched = Vec::new();
+    let mut fetch = |id: &gix_hash::oid| {
+        fetched.push(id.to_owned());
+        std::fs::write(&loose_tree, &loose_tree_data)?;
+        Ok(())
+    };
+    let err = Spec::from_bstr_with_fetch("HEAD^{tree}", &repo, &mut fetch, Options::default()).unwrap_err();
+    assert!(
+        matches!(err, Error::PeelToKind(_)),
+        "fetching must be enabled in the options as well: {err:?}"
+    );
+    assert_eq!(
+        Spec::from_bstr_with_fetch("HEAD^{tree}", &repo, &mut fetch, opts)?,
+        Spec::from_id(tree_id.attach(&repo)),
+        "once fetched, the object is available"
+    );
+    assert_eq!(fetched, [tree_id], "only the missing object was fetched, and only once");
+    Ok(())
+}
+
+#[test]
+fn replaced_objects_keep_their_id_but_provide_the_replacement_content() -> crate::Result {
+    let repo = &repo("replaced")?;
+    let original = repo.head_id()?;
+    let replacement = repo.find_reference("replacement")?.id();
+
+    for spec in ["@^{object}", "@^{commit}"] {
+        assert_eq!(
+            parse_spec(spec, repo)?,
+            Spec::from_id(original),
+            "{spec}: just like `git`, the id of the replaced object is retained"
+        );
+    }
+    assert_eq!(
+        parse_spec("@^{tree}", repo)?,
+        parse_spec_no_baseline("replacement^{tree}", repo)?,
+        "the content of the replacement is used transparently"
+    );
+    assert!(
+        parse_spec("@:file", repo).is_ok(),
+        "paths are looked up in the tree of the replacement"
+    );
+    assert_eq!(
+        parse_spec("@^{/replacement}", repo)?,
+        Spec::from_id(original),
+        "the message of the replacement is searched"
+    );
+    assert_ne!(original, replacement);
+    Ok(())
+}
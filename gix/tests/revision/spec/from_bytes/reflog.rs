@@ -1,10 +1,13 @@
 use gix::{
     prelude::ObjectIdExt,
-    revision::{spec::parse::Error, Spec},
+    revision::{
+        spec::parse::{Error, Warning},
+        Spec,
+    },
 };
 
 use crate::{
-    revision::spec::from_bytes::{parse_spec, parse_spec_no_baseline, repo},
+    revision::spec::from_bytes::{parse_spec, parse_spec_no_baseline, parse_spec_no_baseline_opts, repo},
     util::hex_to_id,
 };
 
@@ -28,6 +31,58 @@ fn nth_prior_checkout() {
         parse_spec("@{-6}", &repo).unwrap_err().to_string(),
         "HEAD has 5 prior checkouts and checkout number 6 is out of range"
     );
+    assert_eq!(
+        parse_spec_no_baseline("@{-0}", &repo).unwrap_err().to_string(),
+        "Negative zeroes are invalid: \"-0\" - remove the '-'",
+        "like git, there is no 0th prior checkout"
+    );
+}
+
+#[test]
+fn nth_prior_checkout_in_bare_repository() -> crate::Result {
+    let bare = repo("complex_graph_bare")?;
+    assert_eq!(
+        parse_spec("@{-1}", &bare).unwrap_err().to_string(),
+        "Reference \"HEAD\" does not have a reference log, cannot search prior checked out branch",
+        "bare clones have no reflog to learn about prior checkouts from"
+    );
+    assert_eq!(
+        parse_spec("main~1", &bare)?.single().expect("single"),
+        parse_spec_no_baseline("main~1", &repo("complex_graph")?)?
+            .single()
+            .expect("single"),
+        "references and history are available as usual"
+    );
+    Ok(())
+}
+
+#[test]
+fn nth_prior_checkout_ref_chain() -> crate::Result {
+    let repo = repo("complex_graph").unwrap();
+    assert!(
+        parse_spec_no_baseline("@{-1}", &repo)?.ref_chain().is_empty(),
+        "the chain isn't collected by default"
+    );
+
+    let opts = gix::revision::spec::parse::Options {
+        ref_chain: true,
+        ..Default::default()
+    };
+    for (spec, expected) in [
+        ("@{-1}", &["HEAD", "refs/heads/i"][..]),
+        ("@{-1}..main", &["HEAD", "refs/heads/i", "refs/heads/main"]),
+        ("@{upstream}", &["HEAD", "refs/heads/main", "refs/remotes/origin/main"]),
+        ("main@{upstream}", &["refs/heads/main", "refs/remotes/origin/main"]),
+    ] {
+        let spec_obj = parse_spec_no_baseline_opts(spec, &repo, opts)?;
+        let actual: Vec<_> = spec_obj
+            .ref_chain()
+            .iter()
+            .map(|r| r.name.as_bstr().to_string())
+            .collect();
+        assert_eq!(actual, expected, "{spec}: all consulted references are listed in order");
+    }
+    Ok(())
 }
 
 #[test]
@@ -78,10 +133,171 @@ fn by_index() {
 }
 
 #[test]
-fn by_date_is_planned_until_git_date_crate_is_implements_parsing() {
-    let repo = repo("complex_graph").unwrap();
+fn by_index_uses_the_reflog_of_the_bound_reference_or_of_the_branch_head_points_to() -> crate::Result {
+    let repo = &repo("complex_graph")?;
+    let main = parse_spec("main@{2}", repo)?;
+    assert_eq!(
+        main.first_reference().expect("set").name.as_bstr(),
+        "refs/heads/main",
+        "the reference is bound before the reflog is consulted"
+    );
+
+    let implied = parse_spec("@{2}", repo)?;
+    assert_eq!(
+        implied, main,
+        "without a bound reference, the branch HEAD points to is used, like in git"
+    );
+    assert_eq!(
+        implied.first_reference().expect("set").name.as_bstr(),
+        "refs/heads/main"
+    );
+
+    let head = parse_spec("HEAD@{2}", repo)?;
+    assert_eq!(head.first_reference().expect("set").name.as_bstr(), "HEAD");
+    assert_ne!(
+        head, main,
+        "HEAD has its own reflog which also records checkouts, and it's used if HEAD is bound explicitly"
+    );
+    Ok(())
+}
+
+#[test]
+fn by_index_zero_is_the_reflog_tip() -> crate::Result {
+    let repo = &repo("reflog_after_reset")?;
+    let head = repo.head_id()?.detach();
+    let before_reset = repo.rev_parse_single("main@{1}")?.detach();
+    assert_ne!(head, before_reset, "the reset moved main back by one commit");
+
+    for spec in ["@{0}", "HEAD@{0}", "main@{0}"] {
+        assert_eq!(
+            parse_spec(spec, repo)?.single().map(gix::Id::detach),
+            Some(head),
+            "{spec} is the commit we reset to"
+        );
+    }
+
+    let unlogged = repo.find_reference("unlogged")?.id().detach();
+    assert_eq!(
+        parse_spec("unlogged@{0}", repo)?.single().map(gix::Id::detach),
+        Some(head),
+        "entry 0 is the value the reflog recorded last…"
+    );
+    assert_ne!(
+        unlogged, head,
+        "…which isn't the current value if the reference moved without logging"
+    );
+
+    assert_eq!(
+        parse_spec("no-log@{0}", repo).unwrap_err().to_string(),
+        "Reference \"refs/heads/no-log\" does not have a reference log, cannot lookup entry"
+    );
+    assert_eq!(
+        parse_spec_no_baseline("empty-log@{0}", repo).unwrap_err().to_string(),
+        "Reference \"refs/heads/empty-log\" has a reference log without entries, cannot lookup entry",
+        "git falls back to the value of the reference here, but we don't guess"
+    );
+    Ok(())
+}
+
+#[test]
+fn by_negative_index_is_invalid_but_the_oldest_entries_are_accessible_through_the_reference() -> crate::Result {
+    let repo = &repo("reflog_after_reset")?;
+    assert_eq!(
+        parse_spec_no_baseline("main@{-1}", repo).unwrap_err().to_string(),
+        "A reference name must be followed by positive numbers in '@{n}', got \"-1\"",
+        "just like git, there is no syntax to count from the oldest entry"
+    );
+
+    let first = repo.rev_parse_single("main~0")?.detach();
+    let second = repo.rev_parse_single("main@{1}")?.detach();
+    for (name, n, expected) in [
+        ("main", 1, Some(first)),
+        ("main", 2, Some(second)),
+        ("main", 3, Some(first)),
+        ("main", 4, None),
+        ("main", 0, None),
+        ("HEAD", 2, Some(second)),
+        ("empty-log", 1, None),
+        ("no-log", 1, None),
+    ] {
+        let reference = repo.find_reference(name)?;
+        assert_eq!(
+            reference.log_entry_from_oldest(n)?.map(|line| line.new_oid),
+            expected,
+            "{name}, {n}: the log has 'commit (initial)', 'commit', 'reset' in that order"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn by_date() -> crate::Result {
+    let repo = repo("reflog_dates")?;
+    let first = repo.rev_parse_single("main~2")?.detach();
+    let second = repo.rev_parse_single("main~1")?.detach();
+    let third = repo.rev_parse_single("main")?.detach();
+
+    for (spec, expected) in [
+        ("main@{2001-09-09 01:46:40 +0000}", first),
+        ("main@{2005-01-01 00:00:00 +0000}", second),
+        ("main@{2020-01-01 00:00:00 +0000}", third),
+    ] {
+        let actual = parse_spec(spec, &repo)?;
+        assert_eq!(actual, Spec::from_id(expected.attach(&repo)), "{spec}");
+        assert_eq!(
+            actual.warnings(),
+            &[],
+            "{spec}: dates within or past the reflog are fine"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn by_date_before_oldest_entry_uses_oldest_value_with_warning() -> crate::Result {
+    let repo = repo("reflog_dates")?;
+    for (spec, expected, reference) in [
+        ("main@{1990-01-01 00:00:00 +0000}", "main~2", "refs/heads/main"),
+        (
+            "truncated@{1990-01-01 00:00:00 +0000}",
+            "truncated~2",
+            "refs/heads/truncated",
+        ),
+    ] {
+        let actual = parse_spec(spec, &repo)?;
+        assert_eq!(
+            actual,
+            Spec::from_id(repo.rev_parse_single(expected)?),
+            "{spec}: the previous value of the oldest entry is used, if there is one"
+        );
+        assert_eq!(actual.warnings().len(), 1, "{spec}");
+        let Warning::RefLogDatePrecedesOldestEntry {
+            reference: actual_reference,
+            desired,
+            oldest,
+        } = &actual.warnings()[0]
+        else {
+            panic!("{spec}: expected a warning about the reflog date")
+        };
+        assert_eq!(actual_reference, reference);
+        assert_eq!(desired.seconds, 631152000);
+        assert_eq!(
+            oldest.seconds,
+            if reference.ends_with("main") {
+                1000000000
+            } else {
+                1100000000
+            }
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn by_date_on_unborn_head() {
+    let repo = repo("new").unwrap();
     assert!(matches!(
-        parse_spec_no_baseline("main@{1979-02-26 18:30:00}", &repo).unwrap_err(),
-        Error::Planned { .. }
+        parse_spec_no_baseline("@{1979-02-26 18:30:00 +0000}", &repo).unwrap_err(),
+        Error::UnbornHeadsHaveNoRefLog
     ));
 }
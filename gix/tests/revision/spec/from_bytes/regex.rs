@@ -116,4 +116,32 @@ mod find_youngest_matching_commit {
             "negations work as well"
         );
     }
+
+    #[test]
+    fn negations_fail_if_all_commits_match() {
+        let repo = repo("all_commits_match").unwrap();
+        let kind = if cfg!(feature = "revparse-regex") {
+            "regex"
+        } else {
+            "text"
+        };
+
+        assert_eq!(
+            parse_spec(":/!-release", &repo).unwrap_err().to_string(),
+            format!("None of 3 commits reached from all references matched {kind} \"!-release\""),
+            "the negation is retained to make clear what was searched for"
+        );
+        assert_eq!(
+            parse_spec("@^{/!-release}", &repo).unwrap_err().to_string(),
+            format!(
+                "None of 3 commits from {} matched {kind} \"!-release\"",
+                repo.head_id().unwrap().shorten_or_id()
+            )
+        );
+        assert_eq!(
+            parse_spec(":/!-nothing", &repo).unwrap(),
+            Spec::from_id(repo.head_id().unwrap()),
+            "the youngest commit not matching is chosen"
+        );
+    }
 }
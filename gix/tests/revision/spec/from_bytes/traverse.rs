@@ -1,7 +1,7 @@
 use gix::{prelude::ObjectIdExt, revision::Spec};
 
 use crate::{
-    revision::spec::from_bytes::{parse_spec, parse_spec_no_baseline, repo},
+    revision::spec::from_bytes::{parse_spec, parse_spec_no_baseline, parse_spec_no_baseline_opts, repo},
     util::hex_to_id,
 };
 
@@ -74,3 +74,130 @@ fn ancestors() {
         "Commit 55e825e has 3 ancestors along the first parent and ancestor number 42 is out of range"
     );
 }
+
+#[test]
+fn ancestors_are_found_across_commits_inside_and_outside_of_the_commit_graph() -> crate::Result {
+    let repo = repo("linear_with_partial_commit_graph")?;
+    let graph = repo.commit_graph()?;
+    let head = repo.head_id()?;
+    assert!(
+        graph.commit_by_id(head).is_none(),
+        "the newest commits aren't in the graph"
+    );
+
+    for (spec, expected_title) in [("@~1", "not in graph 1"), ("@~2", "in graph 5"), ("@~6", "in graph 1")] {
+        let id = parse_spec(spec, &repo)?.single().expect("single");
+        assert_eq!(
+            id.object()?.into_commit().message()?.summary().as_ref(),
+            expected_title,
+            "{spec}"
+        );
+    }
+    assert_eq!(
+        parse_spec("@~7", &repo).unwrap_err().to_string(),
+        format!(
+            "Commit {} has 6 ancestors along the first parent and ancestor number 7 is out of range",
+            head.shorten()?
+        ),
+        "ancestors are counted across both"
+    );
+    assert_eq!(
+        parse_spec_no_baseline("@^{tree}~1", &repo).unwrap_err().to_string(),
+        format!(
+            "Cannot traverse the ancestry of tree {} as it isn't a commit or a tag pointing to one",
+            head.object()?.peel_to_tree()?.id().shorten()?
+        ),
+        "only commits have ancestors"
+    );
+    Ok(())
+}
+
+#[test]
+fn trees_can_be_navigated_on_request_if_obtained_from_commits() -> crate::Result {
+    let repo = repo("complex_graph")?;
+    let opts = gix::revision::spec::parse::Options {
+        allow_tree_navigation: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_spec_no_baseline("a^{tree}^", &repo).unwrap_err().to_string(),
+        "Cannot traverse the ancestry of tree 020e05b as it isn't a commit or a tag pointing to one",
+        "by default, trees can't be navigated"
+    );
+
+    assert_eq!(
+        parse_spec_no_baseline_opts("a^{tree}^", &repo, opts)?,
+        parse_spec_no_baseline("a^^{tree}", &repo)?
+    );
+    assert_eq!(
+        parse_spec_no_baseline_opts("a^{tree}^2", &repo, opts)?,
+        parse_spec_no_baseline("a^2^{tree}", &repo)?
+    );
+    assert_eq!(
+        parse_spec_no_baseline_opts("a^{tree}~1~1", &repo, opts)?,
+        parse_spec_no_baseline("a~2^{tree}", &repo)?,
+        "navigation can be chained"
+    );
+
+    let tree_id = parse_spec_no_baseline("a^{tree}", &repo)?
+        .single()
+        .expect("single")
+        .to_string();
+    assert_eq!(
+        parse_spec_no_baseline_opts(&format!("{tree_id}^"), &repo, opts)
+            .unwrap_err()
+            .to_string(),
+        "Cannot traverse the ancestry of tree 020e05b as it isn't a commit or a tag pointing to one",
+        "trees that weren't obtained from a commit can't be navigated"
+    );
+    Ok(())
+}
+
+#[test]
+fn only_commits_and_tags_pointing_to_them_can_be_traversed() -> crate::Result {
+    use gix::revision::spec::parse::Error;
+    let repo = repo("complex_graph")?;
+    assert_eq!(
+        parse_spec("b-tag~1", &repo)?,
+        parse_spec_no_baseline("b-tag^{commit}~1", &repo)?,
+        "tags are peeled to the commit they point to"
+    );
+    assert_eq!(
+        parse_spec("b-tag^", &repo)?,
+        parse_spec_no_baseline("b-tag^{commit}^", &repo)?
+    );
+
+    let blob = parse_spec_no_baseline("@:file", &repo)?
+        .single()
+        .expect("single")
+        .to_string();
+    for spec in [format!("{blob}~1"), format!("{blob}^")] {
+        let err = parse_spec_no_baseline(&spec, &repo).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::CannotTraverseNonCommit {
+                    actual: gix::object::Kind::Blob,
+                    ..
+                }
+            ),
+            "{spec}: blobs have no ancestors, got {err:?}"
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Cannot traverse the ancestry of blob {} as it isn't a commit or a tag pointing to one",
+                &blob[..7]
+            )
+        );
+    }
+
+    assert!(
+        matches!(
+            parse_spec_no_baseline("@:file~1", &repo).unwrap_err(),
+            Error::PathNotFound { .. }
+        ),
+        "like in git, everything after the colon is part of the path, so the blob is never traversed"
+    );
+    Ok(())
+}
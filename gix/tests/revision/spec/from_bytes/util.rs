@@ -28,7 +28,9 @@ static BASELINE: Lazy<HashMap<PathBuf, HashMap<BString, Option<gix_revision::Spe
             Kind::ExcludeReachable | Kind::IncludeReachable => 1,
             Kind::RangeBetween => 2,
             Kind::ReachableToMergeBase => 3,
-            Kind::IncludeReachableFromParents | Kind::ExcludeReachableFromParents => return None,
+            Kind::IncludeReachableFromParents
+            | Kind::ExcludeReachableFromParents
+            | Kind::ExcludeReachableFromParentsOnly => return None,
         })
     }
     fn object_id_of_next(lines: &mut std::iter::Peekable<bstr::Lines<'_>>) -> gix_hash::ObjectId {
@@ -200,3 +202,9 @@ pub fn repo(name: &str) -> crate::Result<gix::Repository> {
     let base = gix_testtools::scripted_fixture_read_only(FIXTURE_NAME)?;
     Ok(gix::open(base.join(name))?)
 }
+
+pub fn repo_rw(name: &str) -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+    let base = gix_testtools::scripted_fixture_writable(FIXTURE_NAME)?;
+    let repo = gix::open_opts(base.path().join(name), crate::util::restricted())?;
+    Ok((repo, base))
+}
@@ -1 +1,2 @@
+mod commits;
 mod from_bytes;
@@ -1026,11 +1026,12 @@ pub fn main() -> Result<()> {
                 progress,
                 progress_keep_open,
                 None,
-                move |_progress, out, _err| {
+                move |_progress, out, err| {
                     core::repository::revision::resolve(
                         repository(Mode::Strict)?,
                         specs,
                         out,
+                        err,
                         core::repository::revision::resolve::Options {
                             format,
                             explain,